@@ -1,35 +1,70 @@
 // Simplified Polymarket API client
 
-use crate::models::{Market, MarketDetails, OrderRequest, OrderResponse};
+use crate::models::{Market, MarketDetails, OpenOrder, OrderBook, OrderFill, OrderRequest, OrderResponse};
 use anyhow::{Result, Context};
 use reqwest::Client;
 use serde_json::Value;
 use rust_decimal::Decimal;
+use std::future::Future;
 use std::str::FromStr;
+use tokio::sync::Mutex;
+use tracing::warn;
 
 // Polymarket SDK imports for order placement
 use polymarket_client_sdk::clob::{Client as ClobClient, Config as ClobConfig};
-use polymarket_client_sdk::clob::types::{Side, SignatureType};
+use polymarket_client_sdk::clob::types::{Side, SignatureType, TraderSide};
 use polymarket_client_sdk::POLYGON;
-use alloy::signers::local::LocalSigner;
+use polymarket_client_sdk::auth::{state::Authenticated, Normal};
+use polymarket_client_sdk::error::{Status as SdkStatus, StatusCode};
+use alloy::signers::local::{LocalSigner, PrivateKeySigner};
 use alloy::signers::Signer as _;
 use alloy::primitives::Address as AlloyAddress;
-use polymarket_client_sdk::clob::types::request::BalanceAllowanceRequest;
+use polymarket_client_sdk::clob::types::request::{BalanceAllowanceRequest, OrdersRequest, TradesRequest};
 use polymarket_client_sdk::clob::types::AssetType;
+use alloy::primitives::{address, B256, U256};
+use alloy::providers::ProviderBuilder;
+use rust_decimal::prelude::ToPrimitive;
+
+/// Conditional Tokens Framework contract on Polygon (holds the Up/Down outcome tokens)
+const CONDITIONAL_TOKENS: AlloyAddress = address!("4D97DCd97eC945f40cF65F87097ACe5EA0476045");
+/// USDC.e, the collateral Polymarket markets are split from
+const USDC_COLLATERAL: AlloyAddress = address!("2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
+
+alloy::sol! {
+    #[sol(rpc)]
+    interface IConditionalTokens {
+        function mergePositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] partition, uint256 amount) external;
+    }
+}
+
+/// Authenticated CLOB client and the signer its orders are signed with
+#[derive(Clone)]
+struct ClobSession {
+    client: ClobClient<Authenticated<Normal>>,
+    signer: PrivateKeySigner,
+}
 
 pub struct PolymarketApi {
     client: Client,
     gamma_url: String,
     clob_url: String,
     api_key: Option<String>,
+    // L2 credentials are accepted for config compatibility; the SDK derives its own on authenticate()
+    #[allow(dead_code)]
     api_secret: Option<String>,
+    #[allow(dead_code)]
     api_passphrase: Option<String>,
     private_key: Option<String>,
     proxy_wallet_address: Option<String>,
     signature_type: Option<u8>,
+    /// Polygon JSON-RPC endpoint for on-chain calls (merging positions)
+    rpc_url: String,
+    /// Authenticated on first use, dropped when the CLOB rejects its credentials
+    clob: Mutex<Option<ClobSession>>,
 }
 
 impl PolymarketApi {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         gamma_url: String,
         clob_url: String,
@@ -39,6 +74,7 @@ impl PolymarketApi {
         private_key: Option<String>,
         proxy_wallet_address: Option<String>,
         signature_type: Option<u8>,
+        rpc_url: String,
     ) -> Self {
         let client = Client::builder()
             .timeout(std::time::Duration::from_secs(10))
@@ -55,6 +91,8 @@ impl PolymarketApi {
             private_key,
             proxy_wallet_address,
             signature_type,
+            rpc_url,
+            clob: Mutex::new(None),
         }
     }
 
@@ -152,40 +190,67 @@ impl PolymarketApi {
         Ok(price)
     }
 
-    /// Place an order using the official Polymarket SDK
-    /// This method creates, signs, and posts orders to the CLOB
-    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse> {
+    /// Fetch the order book for a token from CLOB /book, keeping the best `depth` levels per side.
+    /// Bids are returned highest first and asks lowest first.
+    pub async fn get_order_book(&self, token_id: &str, depth: usize) -> Result<OrderBook> {
+        let url = format!("{}/book", self.clob_url);
+        let mut request = self.client.get(&url).query(&[("token_id", token_id)]);
+
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Failed to fetch order book for token {} (status: {})", token_id, status);
+        }
+
+        let mut book: OrderBook = response
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid order book response for token {}: {}", token_id, e))?;
+
+        // The API does not guarantee best-first ordering, so normalise it here
+        book.bids.sort_by_key(|l| std::cmp::Reverse(l.price));
+        book.asks.sort_by_key(|l| l.price);
+        book.bids.retain(|l| !l.size.is_zero());
+        book.asks.retain(|l| !l.size.is_zero());
+        book.bids.truncate(depth);
+        book.asks.truncate(depth);
+
+        Ok(book)
+    }
+
+    /// Signer for the configured private key
+    fn signer(&self) -> Result<PrivateKeySigner> {
         let private_key = self.private_key.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Private key required for order signing"))?;
-        
-        // Create signer from private key
-        let signer = LocalSigner::from_str(private_key)
+            .ok_or_else(|| anyhow::anyhow!("Private key required for authenticated CLOB calls and on-chain transactions"))?;
+        Ok(LocalSigner::from_str(private_key)
             .context("Failed to create signer from private key. Ensure private_key is a valid hex string.")?
-            .with_chain_id(Some(POLYGON));
-        
-        // Build authentication builder
+            .with_chain_id(Some(POLYGON)))
+    }
+
+    /// Run the CLOB authentication handshake for the configured wallet.
+    ///
+    /// With a proxy wallet, orders are signed for it as funder (signature_type 1 = Proxy, the
+    /// default, or 2 = GnosisSafe); without one, only signature_type 0 (EOA) is valid.
+    async fn authenticate(&self) -> Result<ClobSession> {
+        let signer = self.signer()?;
         let mut auth_builder = ClobClient::new(&self.clob_url, ClobConfig::default())
             .context("Failed to create CLOB client")?
             .authentication_builder(&signer);
-        
-        // Configure proxy wallet if provided
+
         if let Some(proxy_addr) = &self.proxy_wallet_address {
             let funder_address = AlloyAddress::parse_checksummed(proxy_addr, None)
                 .context(format!("Failed to parse proxy_wallet_address: {}. Ensure it's a valid Ethereum address.", proxy_addr))?;
-            
-            auth_builder = auth_builder.funder(funder_address);
-            
-            // Set signature type based on config
             let sig_type = match self.signature_type {
-                Some(1) => SignatureType::Proxy,
+                Some(1) | Some(0) | None => SignatureType::Proxy,
                 Some(2) => SignatureType::GnosisSafe,
-                Some(0) | None => SignatureType::Proxy, // Default to Proxy when proxy wallet is set
                 Some(n) => anyhow::bail!("Invalid signature_type: {}. Must be 0 (EOA), 1 (Proxy), or 2 (GnosisSafe)", n),
             };
-            
-            auth_builder = auth_builder.signature_type(sig_type);
+            auth_builder = auth_builder.funder(funder_address).signature_type(sig_type);
         } else if let Some(sig_type_num) = self.signature_type {
-            // If signature type is set but no proxy wallet, validate it's EOA
             let sig_type = match sig_type_num {
                 0 => SignatureType::Eoa,
                 1 | 2 => anyhow::bail!("signature_type {} requires proxy_wallet_address to be set", sig_type_num),
@@ -193,52 +258,84 @@ impl PolymarketApi {
             };
             auth_builder = auth_builder.signature_type(sig_type);
         }
-        
-        // Authenticate with CLOB API
+
         let client = auth_builder
             .authenticate()
             .await
-            .context("Failed to authenticate with CLOB API. Check your API credentials (api_key, api_secret, api_passphrase).")?;
-        
+            .context("Failed to authenticate with CLOB API. Check private_key, proxy_wallet_address and signature_type.")?;
+        Ok(ClobSession { client, signer })
+    }
+
+    /// The authenticated CLOB client, authenticating on first use
+    async fn clob(&self) -> Result<ClobSession> {
+        let mut cached = self.clob.lock().await;
+        if let Some(session) = cached.as_ref() {
+            return Ok(session.clone());
+        }
+        let session = self.authenticate().await?;
+        *cached = Some(session.clone());
+        Ok(session)
+    }
+
+    /// Run `call` with the cached CLOB client. If the CLOB rejects our credentials (HTTP 401),
+    /// authenticate again and retry once.
+    async fn with_clob<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(ClobSession) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match call(self.clob().await?).await {
+            Err(e) if is_auth_error(&e) => {
+                warn!("CLOB credentials rejected ({}); re-authenticating", e);
+                *self.clob.lock().await = None;
+                call(self.clob().await?).await
+            }
+            result => result,
+        }
+    }
+
+    /// Place an order using the official Polymarket SDK
+    /// This method creates, signs, and posts orders to the CLOB
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse> {
         // Convert order side string to SDK Side enum
         let side = match order.side.as_str() {
             "BUY" => Side::Buy,
             "SELL" => Side::Sell,
             _ => anyhow::bail!("Invalid order side: {}. Must be 'BUY' or 'SELL'", order.side),
         };
-        
+
         // Parse price and size to Decimal
         let price = Decimal::from_str(&order.price)
             .context(format!("Failed to parse price: {}", order.price))?;
         let size = Decimal::from_str(&order.size)
             .context(format!("Failed to parse size: {}", order.size))?;
-        
-        // Create and sign order using SDK
-        let order_builder = client
-            .limit_order()
-            .token_id(&order.token_id)
-            .size(size)
-            .price(price)
-            .side(side);
-        
-        let signed_order = client.sign(&signer, order_builder.build().await?)
-            .await
-            .context("Failed to sign order")?;
-        
-        // Post order to CLOB
-        let response = match client.post_order(signed_order).await {
-            Ok(resp) => resp,
-            Err(e) => {
-                anyhow::bail!("Failed to post order: {}", e);
-            }
-        };
-        
+
+        let response = self.with_clob(|clob| async move {
+            // Create and sign order using SDK
+            let order_builder = clob.client
+                .limit_order()
+                .token_id(&order.token_id)
+                .size(size)
+                .price(price)
+                .side(side);
+
+            let signed_order = clob.client.sign(&clob.signer, order_builder.build().await?)
+                .await
+                .context("Failed to sign order")?;
+
+            // Post order to CLOB
+            clob.client.post_order(signed_order).await.map_err(|e| {
+                let msg = format!("Failed to post order: {}", e);
+                anyhow::Error::new(e).context(msg)
+            })
+        }).await?;
+
         // Check if the response indicates failure
         if !response.success {
             let error_msg = response.error_msg.as_deref().unwrap_or("Unknown error");
             anyhow::bail!("Order was rejected: {}", error_msg);
         }
-        
+
         // Convert SDK response to our OrderResponse format
         Ok(OrderResponse {
             success: response.success,
@@ -251,109 +348,182 @@ impl PolymarketApi {
 
     /// Cancel an order by order ID
     pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
-        let private_key = self.private_key.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Private key required for order cancellation"))?;
-        
-        // Create signer from private key
-        let signer = LocalSigner::from_str(private_key)
-            .context("Failed to create signer from private key")?
-            .with_chain_id(Some(POLYGON));
-        
-        // Build authentication builder
-        let mut auth_builder = ClobClient::new(&self.clob_url, ClobConfig::default())
-            .context("Failed to create CLOB client")?
-            .authentication_builder(&signer);
-        
-        // Configure proxy wallet if provided
-        if let Some(proxy_addr) = &self.proxy_wallet_address {
-            let funder_address = AlloyAddress::parse_checksummed(proxy_addr, None)
-                .context("Failed to parse proxy_wallet_address")?;
-            
-            auth_builder = auth_builder.funder(funder_address);
-            
-            let sig_type = match self.signature_type {
-                Some(1) => SignatureType::Proxy,
-                Some(2) => SignatureType::GnosisSafe,
-                Some(0) | None => SignatureType::Proxy,
-                _ => SignatureType::Proxy,
-            };
-            auth_builder = auth_builder.signature_type(sig_type);
-        } else if let Some(sig_type_num) = self.signature_type {
-            let sig_type = match sig_type_num {
-                0 => SignatureType::Eoa,
-                _ => SignatureType::Eoa,
-            };
-            auth_builder = auth_builder.signature_type(sig_type);
-        }
-        
-        // Authenticate with CLOB API
-        let client = auth_builder
-            .authenticate()
-            .await
-            .context("Failed to authenticate with CLOB API")?;
-        
-        // Cancel the order
-        client.cancel_order(order_id).await
-            .context(format!("Failed to cancel order {}", order_id))?;
-        
-        Ok(())
+        self.with_clob(|clob| async move {
+            clob.client.cancel_order(order_id).await
+                .context(format!("Failed to cancel order {}", order_id))?;
+            Ok(())
+        }).await
     }
 
     /// Check conditional token balance only (shares) for a token_id.
     ///
-    /// Used in LIVE mode to confirm entry fills by observing real balance changes.
+    /// Used in LIVE mode to reconcile positions with real balances.
     pub async fn check_balance_only(&self, token_id: &str) -> Result<Decimal> {
-        let private_key = self.private_key.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Private key required for balance check"))?;
-
-        // Create signer from private key
-        let signer = LocalSigner::from_str(private_key)
-            .context("Failed to create signer from private key. Ensure private_key is a valid hex string.")?
-            .with_chain_id(Some(POLYGON));
-
-        // Build authentication builder
-        let mut auth_builder = ClobClient::new(&self.clob_url, ClobConfig::default())
-            .context("Failed to create CLOB client")?
-            .authentication_builder(&signer);
+        let request = BalanceAllowanceRequest::builder()
+            .token_id(token_id.to_string())
+            .asset_type(AssetType::Conditional)
+            .build();
 
-        // Configure proxy wallet if provided
-        if let Some(proxy_addr) = &self.proxy_wallet_address {
-            let funder_address = AlloyAddress::parse_checksummed(proxy_addr, None)
-                .context(format!("Failed to parse proxy_wallet_address: {}. Ensure it's a valid Ethereum address.", proxy_addr))?;
-            auth_builder = auth_builder.funder(funder_address);
+        self.with_clob(|clob| {
+            let request = request.clone();
+            async move {
+                let balance_allowance = clob.client
+                    .balance_allowance(request)
+                    .await
+                    .context("Failed to fetch balance")?;
+                Ok(balance_allowance.balance)
+            }
+        }).await
+    }
 
-            let sig_type = match self.signature_type {
-                Some(1) => SignatureType::Proxy,
-                Some(2) => SignatureType::GnosisSafe,
-                Some(0) | None => SignatureType::Proxy,
-                Some(n) => anyhow::bail!("Invalid signature_type: {}. Must be 0 (EOA), 1 (Proxy), or 2 (GnosisSafe)", n),
-            };
-            auth_builder = auth_builder.signature_type(sig_type);
-        } else if let Some(sig_type_num) = self.signature_type {
-            let sig_type = match sig_type_num {
-                0 => SignatureType::Eoa,
-                1 | 2 => anyhow::bail!("signature_type {} requires proxy_wallet_address to be set", sig_type_num),
-                n => anyhow::bail!("Invalid signature_type: {}. Must be 0 (EOA), 1 (Proxy), or 2 (GnosisSafe)", n),
-            };
-            auth_builder = auth_builder.signature_type(sig_type);
-        }
+    /// Open orders of this account in one market (condition ID), across all result pages.
+    ///
+    /// Used in LIVE mode to reconcile a journaled session after a restart.
+    pub async fn open_orders(&self, condition_id: &str) -> Result<Vec<OpenOrder>> {
+        let request = OrdersRequest::builder().market(condition_id.to_string()).build();
+        self.with_clob(|clob| {
+            let request = &request;
+            async move {
+                let mut orders = Vec::new();
+                let mut cursor: Option<String> = None;
+                loop {
+                    let page = clob.client
+                        .orders(request, cursor.take())
+                        .await
+                        .context(format!("Failed to fetch open orders for market {}", condition_id))?;
+                    orders.extend(page.data.into_iter().map(|o| OpenOrder {
+                        order_id: o.id,
+                        token_id: o.asset_id,
+                        side: side_str(o.side),
+                        price: o.price,
+                        original_size: o.original_size,
+                        size_matched: o.size_matched,
+                    }));
+                    // "LTE=" marks the last page
+                    if page.next_cursor.is_empty() || page.next_cursor == "LTE=" {
+                        break;
+                    }
+                    cursor = Some(page.next_cursor);
+                }
+                Ok(orders)
+            }
+        }).await
+    }
 
-        // Authenticate with CLOB API
-        let client = auth_builder
-            .authenticate()
-            .await
-            .context("Failed to authenticate with CLOB API for balance check")?;
+    /// Fills of our orders in one market (condition ID) matched at or after `after_secs`
+    pub async fn order_fills(&self, condition_id: &str, after_secs: u64) -> Result<Vec<OrderFill>> {
+        let request = TradesRequest::builder()
+            .market(condition_id.to_string())
+            .after(after_secs as i64)
+            .build();
+        self.fills(&request, &format!("market {}", condition_id)).await
+    }
 
-        let request = BalanceAllowanceRequest::builder()
-            .token_id(token_id.to_string())
-            .asset_type(AssetType::Conditional)
+    /// Fills of our orders on one token matched at or after `after_secs`
+    pub async fn token_fills(&self, token_id: &str, after_secs: u64) -> Result<Vec<OrderFill>> {
+        let request = TradesRequest::builder()
+            .asset_id(token_id.to_string())
+            .after(after_secs as i64)
             .build();
+        self.fills(&request, &format!("token {}", token_id)).await
+    }
+
+    /// Our fills in the trades matching `request`, across all result pages.
+    ///
+    /// A trade where we were the taker gives one fill for the taker order; as maker, one fill per
+    /// maker order of ours it matched.
+    async fn fills(&self, request: &TradesRequest, what: &str) -> Result<Vec<OrderFill>> {
+        self.with_clob(|clob| async move {
+            let mut fills = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = clob.client
+                    .trades(request, cursor.take())
+                    .await
+                    .context(format!("Failed to fetch trades for {}", what))?;
+                for trade in page.data {
+                    let matched_at = trade.match_time.timestamp().max(0) as u64;
+                    match trade.trader_side {
+                        TraderSide::Taker => fills.push(OrderFill {
+                            trade_id: trade.id.clone(),
+                            order_id: trade.taker_order_id.clone(),
+                            token_id: trade.asset_id.clone(),
+                            side: side_str(trade.side),
+                            price: trade.price,
+                            size: trade.size,
+                            matched_at,
+                        }),
+                        // Only the maker orders owned by this API key are ours
+                        _ => fills.extend(trade.maker_orders.iter().filter(|m| m.owner == trade.owner).map(|m| OrderFill {
+                            trade_id: trade.id.clone(),
+                            order_id: m.order_id.clone(),
+                            token_id: m.asset_id.clone(),
+                            side: side_str(m.side),
+                            price: m.price,
+                            size: m.matched_amount,
+                            matched_at,
+                        })),
+                    }
+                }
+                // "LTE=" marks the last page
+                if page.next_cursor.is_empty() || page.next_cursor == "LTE=" {
+                    break;
+                }
+                cursor = Some(page.next_cursor);
+            }
+            Ok(fills)
+        }).await
+    }
 
-        let balance_allowance = client
-            .balance_allowance(request)
+    /// Merge `shares` Up+Down pairs of a market back into USDC (1.0 per pair) on-chain.
+    /// Returns the transaction hash.
+    ///
+    /// The merge is sent from the signing key's own address, so it only works for EOA wallets
+    /// (signature_type 0). Proxy and Safe wallets hold their pairs to resolution instead.
+    pub async fn merge_positions(&self, condition_id: &str, shares: Decimal) -> Result<String> {
+        if self.proxy_wallet_address.is_some() {
+            anyhow::bail!("Merging needs an EOA wallet (signature_type 0); proxy wallet positions are redeemed at resolution");
+        }
+        let signer = self.signer()?;
+        let rpc_url = self.rpc_url.parse()
+            .context(format!("Invalid rpc_url: {}", self.rpc_url))?;
+        let provider = ProviderBuilder::new().wallet(signer).connect_http(rpc_url);
+        let ctf = IConditionalTokens::new(CONDITIONAL_TOKENS, provider);
+
+        let condition = B256::from_str(condition_id)
+            .context(format!("Invalid condition ID: {}", condition_id))?;
+        // Outcome tokens use 6 decimals, like USDC
+        let amount = (shares * Decimal::from(1_000_000u64)).trunc().to_u128()
+            .ok_or_else(|| anyhow::anyhow!("Invalid merge amount: {}", shares))?;
+        // Binary market: index sets 1 (first outcome) and 2 (second outcome)
+        let partition = vec![U256::from(1u8), U256::from(2u8)];
+
+        let receipt = ctf
+            .mergePositions(USDC_COLLATERAL, B256::ZERO, condition, partition, U256::from(amount))
+            .send()
             .await
-            .context("Failed to fetch balance")?;
+            .context("Failed to send mergePositions transaction")?
+            .get_receipt()
+            .await
+            .context("Failed to get mergePositions receipt")?;
+        if !receipt.status() {
+            anyhow::bail!("mergePositions transaction {} reverted", receipt.transaction_hash);
+        }
+        Ok(receipt.transaction_hash.to_string())
+    }
+}
 
-        Ok(balance_allowance.balance)
+fn side_str(side: Side) -> String {
+    match side {
+        Side::Buy => "BUY".to_string(),
+        Side::Sell => "SELL".to_string(),
+        _ => "UNKNOWN".to_string(),
     }
 }
+
+/// Whether the CLOB refused the request for bad or expired credentials
+fn is_auth_error(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<SdkStatus>())
+        .any(|status| status.status_code == StatusCode::UNAUTHORIZED)
+}
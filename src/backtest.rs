@@ -0,0 +1,254 @@
+// Historical backtesting - replays recorded market data through the trading engine with paper execution
+
+use crate::models::{MarketData, TokenPrice};
+use crate::recorder::{read_snapshots, SnapshotRecord, SNAPSHOT_FORMAT_VERSION};
+use crate::replay::{ReplaySource, ReplaySpeed};
+use crate::simulation::{PaperExecutor, SimulationTrader};
+use crate::strategies::Strategy;
+use crate::types::{PricePoint, TradeLog, TradingStats, MARKET_DURATION_SECS};
+use anyhow::{Context, Result};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Load backtest data as snapshots in capture order.
+///
+/// Snapshot recordings (a recorder file or directory) keep their full book depth;
+/// CSV and JSONL price points are converted with `snapshots_from_points`.
+pub fn load_snapshots(path: &Path) -> Result<Vec<SnapshotRecord>> {
+    if path.is_dir() || is_snapshot_recording(path)? {
+        return read_snapshots(path);
+    }
+    Ok(snapshots_from_points(&load_price_points(path)?))
+}
+
+/// Load price points from a CSV (with header) or JSONL file, sorted by timestamp.
+///
+/// CSV columns: `timestamp,up_price,down_price[,actual_outcome][,asset][,news_event]`.
+/// JSONL: one `PricePoint` object per line. Timestamps are UNIX seconds.
+pub fn load_price_points(path: &Path) -> Result<Vec<PricePoint>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read price data {}", path.display()))?;
+
+    let is_csv = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    let mut points = if is_csv {
+        parse_csv(&content)?
+    } else {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str::<PricePoint>(line)
+                    .with_context(|| format!("Invalid price point on line {}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    // Stable sort keeps the recorded order of points sharing a timestamp
+    points.sort_by_key(|p| p.timestamp);
+    Ok(points)
+}
+
+/// Whether a JSONL file was written by the snapshot recorder (checks the first record)
+fn is_snapshot_recording(path: &Path) -> Result<bool> {
+    use std::io::BufRead;
+    let file = fs::File::open(path).with_context(|| format!("Failed to open price data {}", path.display()))?;
+    let first = std::io::BufReader::new(file)
+        .lines()
+        .map_while(|l| l.ok())
+        .find(|l| !l.trim().is_empty());
+    Ok(first
+        .and_then(|l| serde_json::from_str::<serde_json::Value>(&l).ok())
+        .map(|v| v.get("markets").is_some() && v.get("captured_at_ms").is_some())
+        .unwrap_or(false))
+}
+
+/// One snapshot per timestamp, quoting each token's price as its best ask (no depth).
+/// A market's `actual_outcome` is carried on all of its snapshots so settlement can use it.
+pub fn snapshots_from_points(points: &[PricePoint]) -> Vec<SnapshotRecord> {
+    let asset_of = |p: &PricePoint| p.asset.clone().unwrap_or_else(|| "UNKNOWN".to_string());
+    let period_of = |ts: u64| (ts / MARKET_DURATION_SECS) * MARKET_DURATION_SECS;
+    let outcomes: HashMap<(String, u64), u8> = points
+        .iter()
+        .filter_map(|p| Some(((asset_of(p), period_of(p.timestamp)), p.actual_outcome?)))
+        .collect();
+
+    let mut records: Vec<SnapshotRecord> = Vec::new();
+    for point in points {
+        let asset = asset_of(point);
+        let period = period_of(point.timestamp);
+        let captured_at_ms = point.timestamp * 1000;
+        // Points sharing a timestamp form one snapshot; a repeated asset starts the next one
+        if !records
+            .last()
+            .is_some_and(|r| r.captured_at_ms == captured_at_ms && !r.markets.contains_key(&asset))
+        {
+            records.push(SnapshotRecord {
+                v: SNAPSHOT_FORMAT_VERSION,
+                captured_at_ms,
+                period_timestamp: period,
+                time_remaining_seconds: point
+                    .time_remaining_secs
+                    .unwrap_or_else(|| MARKET_DURATION_SECS.saturating_sub(point.timestamp - period)),
+                markets: BTreeMap::new(),
+                outcomes: BTreeMap::new(),
+            });
+        }
+        let Some(record) = records.last_mut() else {
+            continue;
+        };
+
+        let token = |label: &str, price: f64| TokenPrice {
+            token_id: format!("{}-{}-{}", asset, period, label),
+            bid: None,
+            ask: Decimal::from_f64(price).filter(|p| *p > Decimal::ZERO),
+            bids: Vec::new(),
+            asks: Vec::new(),
+        };
+        record.markets.insert(
+            asset.clone(),
+            MarketData {
+                condition_id: format!("{}-{}", asset, period),
+                market_name: asset.clone(),
+                up_token: Some(token("up", point.up_price)),
+                down_token: Some(token("down", point.down_price)),
+            },
+        );
+        if let Some(&outcome) = outcomes.get(&(asset.clone(), period)) {
+            record.outcomes.insert(asset, outcome);
+        }
+    }
+    records
+}
+
+fn parse_csv(content: &str) -> Result<Vec<PricePoint>> {
+    let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("CSV file is empty"))?
+        .split(',')
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+
+    let ts_col = column("timestamp").ok_or_else(|| anyhow::anyhow!("CSV is missing a 'timestamp' column"))?;
+    let up_col = column("up_price").ok_or_else(|| anyhow::anyhow!("CSV is missing an 'up_price' column"))?;
+    let down_col = column("down_price").ok_or_else(|| anyhow::anyhow!("CSV is missing a 'down_price' column"))?;
+    let outcome_col = column("actual_outcome");
+    let asset_col = column("asset");
+    let news_col = column("news_event");
+    let remaining_col = column("time_remaining_secs");
+
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let field = |col: Option<usize>| col.and_then(|c| fields.get(c).copied()).filter(|f| !f.is_empty());
+            let row = i + 2;
+
+            Ok(PricePoint {
+                timestamp: field(Some(ts_col))
+                    .ok_or_else(|| anyhow::anyhow!("Missing timestamp on row {}", row))?
+                    .parse()
+                    .with_context(|| format!("Invalid timestamp on row {}", row))?,
+                up_price: field(Some(up_col))
+                    .ok_or_else(|| anyhow::anyhow!("Missing up_price on row {}", row))?
+                    .parse()
+                    .with_context(|| format!("Invalid up_price on row {}", row))?,
+                down_price: field(Some(down_col))
+                    .ok_or_else(|| anyhow::anyhow!("Missing down_price on row {}", row))?
+                    .parse()
+                    .with_context(|| format!("Invalid down_price on row {}", row))?,
+                actual_outcome: field(outcome_col).map(|v| v.parse()).transpose()
+                    .with_context(|| format!("Invalid actual_outcome on row {}", row))?,
+                asset: field(asset_col).map(|v| v.to_string()),
+                news_event: field(news_col).map(|v| v.parse()).transpose()
+                    .with_context(|| format!("Invalid news_event on row {}", row))?,
+                time_remaining_secs: field(remaining_col).map(|v| v.parse()).transpose()
+                    .with_context(|| format!("Invalid time_remaining_secs on row {}", row))?,
+            })
+        })
+        .collect()
+}
+
+/// Result of a backtest run
+#[derive(Debug, Serialize)]
+pub struct BacktestReport {
+    pub stats: TradingStats,
+    pub trades: Vec<TradeLog>,
+    /// Number of 15-minute markets replayed (per asset)
+    pub markets: usize,
+    /// Number of per-asset quotes replayed
+    pub price_points: usize,
+}
+
+/// Replays snapshots through the simulation trader (the shared engine with paper execution),
+/// so backtests apply exactly the entry filters, fills and exits of a live session.
+pub struct Backtester {
+    strategy: Box<dyn Strategy>,
+    initial_capital: Decimal,
+}
+
+impl Backtester {
+    pub fn new(strategy: Box<dyn Strategy>, initial_capital: Decimal) -> Self {
+        Self { strategy, initial_capital }
+    }
+
+    /// Replay `records` (in capture order) and settle every market still open at the end.
+    /// Blocks on its own runtime, so call it outside async code (or inside `block_in_place`).
+    pub fn run(self, records: Arc<[SnapshotRecord]>) -> Result<BacktestReport> {
+        let has_bids = records.iter().flat_map(|r| r.markets.values()).any(|m| {
+            [&m.up_token, &m.down_token]
+                .into_iter()
+                .any(|t| t.as_ref().is_some_and(|t| t.bid.is_some()))
+        });
+        if self.strategy.needs_bids() && !has_bids {
+            anyhow::bail!(
+                "{} quotes off both sides of the book, but this data has ask prices only; backtest it on a snapshot recording",
+                self.strategy.name()
+            );
+        }
+
+        let markets = records
+            .iter()
+            .flat_map(|r| r.markets.keys().map(move |asset| (asset, r.period_timestamp)))
+            .collect::<BTreeSet<_>>()
+            .len();
+        let price_points = records.iter().map(|r| r.markets.len()).sum();
+        let assets: Vec<String> = records
+            .iter()
+            .flat_map(|r| r.markets.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let source = Arc::new(ReplaySource::new(records, ReplaySpeed::Max));
+        let mut trader = SimulationTrader::for_assets(source, PaperExecutor::default(), self.strategy, assets, self.initial_capital);
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()?
+            .block_on(async {
+                trader.run().await?;
+                trader.end_markets().await;
+                anyhow::Ok(())
+            })?;
+
+        let ledger = trader.into_ledger();
+        Ok(BacktestReport {
+            stats: ledger.stats,
+            trades: ledger.trades,
+            markets,
+            price_points,
+        })
+    }
+}
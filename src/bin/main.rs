@@ -1,79 +1,25 @@
 // Main entry point for trending index trading bot
 
 use anyhow::Result;
-use polymarket_trending_index_trading::config::{CliConfig, Mode};
+use polymarket_trending_index_trading::backtest::{load_snapshots, Backtester};
+use polymarket_trending_index_trading::config::{BacktestArgs, CliConfig, Command, Mode, ReplayArgs, SessionRestart, SweepArgs, WalkForwardArgs};
+use polymarket_trending_index_trading::sweep::{self, ParamRange, RankMetric};
+use polymarket_trending_index_trading::walkforward::{self, WalkForwardConfig};
+use polymarket_trending_index_trading::replay::{ReplaySource, ReplaySpeed};
+use polymarket_trending_index_trading::strategies::{StrategyRegistry, StrategySpec};
+use polymarket_trending_index_trading::recorder::SnapshotRecord;
 use polymarket_trending_index_trading::simulation::SimulationTrader;
 use polymarket_trending_index_trading::trading::LiveTrader;
 use polymarket_trending_index_trading::api::PolymarketApi;
 use polymarket_trending_index_trading::monitor::MarketMonitor;
-use polymarket_trending_index_trading::models::Market;
-use polymarket_trending_index_trading::{init_history_file, log_trading_event};
+use polymarket_trending_index_trading::feed::MarketFeed;
+use polymarket_trending_index_trading::recorder::SnapshotRecorder;
+use polymarket_trending_index_trading::{init_history_file, log_trading_event, mute_events};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::sync::Arc;
 use std::fs::OpenOptions;
 use tracing::{info, error, warn};
-use tracing_subscriber;
-
-/// Discover market for a given asset
-async fn discover_market(
-    api: &PolymarketApi,
-    market_name: &str,
-    slug_prefixes: &[&str],
-    current_time: u64,
-) -> Result<Market> {
-    let rounded_time = (current_time / 900) * 900; // Round to nearest 15 minutes
-
-    for (i, prefix) in slug_prefixes.iter().enumerate() {
-        if i > 0 {
-            info!("🔍 Trying {} market with slug prefix '{}'...", market_name, prefix);
-        }
-
-        // Try current period with this prefix
-        let slug = format!("{}-updown-15m-{}", prefix, rounded_time);
-        if let Ok(market) = api.get_market_by_slug(&slug).await {
-            if market.active && !market.closed {
-                info!("✅ Found {} market by slug: {} | Condition ID: {}", market_name, market.slug, market.condition_id);
-                return Ok(market);
-            }
-        }
-    
-        // Try previous periods with this prefix
-        for offset in 1..=3 {
-            let try_time = rounded_time - (offset * 900);
-            let try_slug = format!("{}-updown-15m-{}", prefix, try_time);
-            info!("Trying previous {} market by slug: {}", market_name, try_slug);
-            if let Ok(market) = api.get_market_by_slug(&try_slug).await {
-                if market.active && !market.closed {
-                    info!("✅ Found {} market by slug: {} | Condition ID: {}", market_name, market.slug, market.condition_id);
-                    return Ok(market);
-                }
-            }
-        }
-    }
-
-    let tried = slug_prefixes.join(", ");
-    anyhow::bail!(
-        "Could not find active {} 15-minute up/down market (tried prefixes: {})",
-        market_name,
-        tried
-    )
-}
-
-/// Create dummy market for fallback
-fn create_dummy_market(name: &str, slug: &str) -> Market {
-    Market {
-        condition_id: format!("dummy_{}_fallback", name.to_lowercase()),
-        market_id: None,
-        question: format!("{} Up/Down 15m (Dummy)", name),
-        slug: slug.to_string(),
-        resolution_source: None,
-        end_date_iso: None,
-        active: false,
-        closed: true,
-        tokens: None,
-    }
-}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -113,6 +59,25 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    // Offline commands don't need the API or market discovery. Batch runs print only their
+    // results and block on their own runtimes.
+    match config.command.clone() {
+        Some(Command::Backtest(args)) => {
+            mute_events();
+            return tokio::task::block_in_place(|| run_backtest(&config, &args));
+        }
+        Some(Command::Replay(args)) => return run_replay(config, &args).await,
+        Some(Command::Sweep(args)) => {
+            mute_events();
+            return tokio::task::block_in_place(|| run_sweep(&config, &args));
+        }
+        Some(Command::WalkForward(args)) => {
+            mute_events();
+            return tokio::task::block_in_place(|| run_walk_forward(&config, &args));
+        }
+        None => {}
+    }
+
     // Create API client
     let api = Arc::new(PolymarketApi::new(
         config.get_gamma_url(),
@@ -123,57 +88,49 @@ async fn main() -> Result<()> {
         config.get_private_key(),
         config.get_proxy_wallet_address(),
         config.get_signature_type(),
+        config.get_rpc_url(),
     ));
 
-    // Find current markets
-    println!("🔍 Discovering current ETH/BTC markets (15m up/down)...");
+    // Build the asset registry and discover the current 15m market for each enabled asset
+    let mut monitor = MarketMonitor::new(api.clone(), config.get_asset_configs())?
+        .with_book_depth(config.get_book_depth());
+    if config.is_websocket_enabled() {
+        let ws_url = config.get_market_ws_url();
+        println!("📡 Streaming prices from {} (REST fallback)", ws_url);
+        monitor = monitor.with_feed(
+            MarketFeed::spawn(ws_url),
+            std::time::Duration::from_millis(config.get_min_update_interval_ms()),
+        );
+    } else {
+        println!("📡 WebSocket feed disabled, polling REST prices");
+    }
+    if let Some(dir) = config.get_recording_dir() {
+        match SnapshotRecorder::new(&dir) {
+            Ok(recorder) => {
+                println!("💾 Recording snapshots to {}", dir.display());
+                monitor = monitor.with_recorder(recorder);
+            }
+            Err(e) => warn!("⚠️  Snapshot recording disabled: {}", e),
+        }
+    }
+    let monitor = Arc::new(monitor);
+    let symbols: Vec<&str> = monitor.registry().enabled().map(|a| a.symbol()).collect();
+    println!("🔍 Discovering current {} markets (15m up/down)...", symbols.join("/"));
     info!("🔍 Finding current markets...");
-    
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    
-    // Discover ETH market
-    let eth_market = discover_market(&api, "ETH", &["eth"], current_time).await
-        .unwrap_or_else(|e| {
-            error!("❌ Could not find active ETH market: {}", e);
-            std::process::exit(1);
-        });
-
-    // Discover BTC market
-    let btc_market = discover_market(&api, "BTC", &["btc"], current_time).await
-        .unwrap_or_else(|e| {
-            error!("❌ Could not find active BTC market: {}", e);
-            std::process::exit(1);
-        });
-
-    // Create dummy markets for Solana and XRP (can be enhanced later)
-    let solana_market = create_dummy_market("Solana", "solana-updown-15m-dummy");
-    let xrp_market = create_dummy_market("XRP", "xrp-updown-15m-dummy");
+
+    if let Err(e) = monitor.discover_markets().await {
+        error!("❌ Could not find active market: {}", e);
+        std::process::exit(1);
+    }
 
     println!("✅ Markets discovered:");
-    println!("   ETH   : {} ({})", eth_market.slug, eth_market.condition_id);
-    println!("   BTC   : {} ({})", btc_market.slug, btc_market.condition_id);
-    println!("   Solana: {} ({})", solana_market.slug, solana_market.condition_id);
-    println!("   XRP   : {} ({})", xrp_market.slug, xrp_market.condition_id);
     info!("✅ Found markets:");
-    info!("   ETH: {} ({})", eth_market.slug, eth_market.condition_id);
-    info!("   BTC: {} ({})", btc_market.slug, btc_market.condition_id);
-    info!("   Solana: {} ({})", solana_market.slug, solana_market.condition_id);
-    info!("   XRP: {} ({})", xrp_market.slug, xrp_market.condition_id);
-
-    // Create market monitor (pass enable flags so it can skip/log per asset)
-    let monitor = Arc::new(MarketMonitor::new(
-        api.clone(),
-        eth_market,
-        btc_market,
-        solana_market,
-        xrp_market,
-        config.is_eth_enabled(),
-        config.is_solana_enabled(),
-        config.is_xrp_enabled(),
-    )?);
+    for asset in monitor.registry().enabled() {
+        if let Some(market) = asset.market().await {
+            println!("   {:<6}: {} ({})", asset.symbol(), market.slug, market.condition_id);
+            info!("   {}: {} ({})", asset.symbol(), market.slug, market.condition_id);
+        }
+    }
 
     // Get strategy configuration
     let strategy_config = config.get_strategy_config();
@@ -189,6 +146,7 @@ async fn main() -> Result<()> {
         strategy_config.trend_threshold,
         strategy_config.momentum_threshold_pct
     );
+    let strategy = strategy_spec(&config)?.build(strategy_config)?;
     let initial_capital = Decimal::try_from(config.initial_capital)
         .unwrap_or(dec!(1000.0));
 
@@ -198,7 +156,7 @@ async fn main() -> Result<()> {
             info!("🎮 Running in SIMULATION MODE (logs and calculations only)");
             let mut trader = SimulationTrader::new(
                 monitor,
-                strategy_config,
+                strategy,
                 config,
                 initial_capital,
             );
@@ -207,16 +165,219 @@ async fn main() -> Result<()> {
         Mode::Live => {
             info!("🚀 Running in LIVE TRADING MODE (monitoring and sending real orders)");
             warn!("⚠️  WARNING: Live trading mode will execute real trades!");
+            let on_restart = config.get_session_on_restart()?;
             let mut trader = LiveTrader::new(
                 monitor,
                 api,
-                strategy_config,
+                strategy,
                 config,
                 initial_capital,
             );
+            trader.recover(on_restart == SessionRestart::Flatten).await?;
             trader.run().await?;
         }
     }
 
     Ok(())
 }
+
+/// The `--strategy` registry entry with its config.json section
+fn strategy_spec(config: &CliConfig) -> Result<StrategySpec> {
+    let name = config.get_strategy_name();
+    StrategyRegistry::builtin().spec(&name, config.get_strategy_section(&name))
+}
+
+/// Load backtest data and keep the assets selected by `--market`
+fn load_selected_snapshots(config: &CliConfig, data: &std::path::Path) -> Result<Arc<[SnapshotRecord]>> {
+    let mut records = load_snapshots(data)?;
+    for record in &mut records {
+        record.markets.retain(|asset, _| config.market_selects(asset));
+    }
+    records.retain(|r| !r.markets.is_empty());
+    if records.is_empty() {
+        anyhow::bail!("No price data for market '{}' in {}", config.market, data.display());
+    }
+    Ok(records.into())
+}
+
+/// Replay recorded price data through the configured strategy and print the resulting stats
+fn run_backtest(config: &CliConfig, args: &BacktestArgs) -> Result<()> {
+    let strategy_config = config.get_strategy_config();
+    let initial_capital = Decimal::try_from(config.initial_capital).unwrap_or(dec!(1000.0));
+
+    let records = load_selected_snapshots(config, &args.data)?;
+
+    println!("🧪 Backtest   : {} ({} snapshots)", args.data.display(), records.len());
+    println!(
+        "Strategy cfg  : index={:?} | threshold={:.2} | mom_thresh={:.2} | TP=+{} | SL=-{} | size={}",
+        strategy_config.index_type,
+        strategy_config.trend_threshold,
+        strategy_config.momentum_threshold_pct,
+        strategy_config.profit_threshold,
+        strategy_config.sl_threshold,
+        strategy_config.position_size_shares
+    );
+
+    let strategy = strategy_spec(config)?.build(strategy_config)?;
+    println!("Strategy      : {}", strategy.name());
+
+    let backtester = Backtester::new(strategy, initial_capital);
+    let report = backtester.run(records)?;
+    let stats = &report.stats;
+
+    let summary = format!(
+        "📊 BACKTEST RESULT | markets={} | trades={} | wins={} | losses={} | win_rate={:.1}% | total_pnl={:.4} | capital={:.2}",
+        report.markets,
+        stats.total_trades,
+        stats.winning_trades,
+        stats.losing_trades,
+        stats.win_rate(),
+        stats.total_pnl,
+        stats.current_capital
+    );
+    println!("{}", summary);
+    log_trading_event(&summary);
+
+    if let Some(output) = &args.output {
+        std::fs::write(output, serde_json::to_string_pretty(&report)?)?;
+        println!("💾 Report written to {}", output.display());
+    }
+
+    Ok(())
+}
+
+/// Run the simulation trader against a recorded session
+async fn run_replay(config: CliConfig, args: &ReplayArgs) -> Result<()> {
+    let speed: ReplaySpeed = args.speed.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+    let source = ReplaySource::from_path(&args.data, speed)?;
+    if source.is_empty() {
+        anyhow::bail!("No snapshots found in {}", args.data.display());
+    }
+    println!("⏯️  Replaying {} snapshots from {} at {:?}", source.len(), args.data.display(), speed);
+
+    let strategy = strategy_spec(&config)?.build(config.get_strategy_config())?;
+    let initial_capital = Decimal::try_from(config.initial_capital).unwrap_or(dec!(1000.0));
+    let mut trader = SimulationTrader::new(Arc::new(source), strategy, config, initial_capital);
+    trader.run().await
+}
+
+/// Grid-search strategy parameters over recorded data and print/export the leaderboard
+fn run_sweep(config: &CliConfig, args: &SweepArgs) -> Result<()> {
+    let ranges = args
+        .params
+        .iter()
+        .map(|p| p.parse::<ParamRange>())
+        .collect::<Result<Vec<_>>>()?;
+    let metric: RankMetric = args.rank_by.parse()?;
+    let records = load_selected_snapshots(config, &args.data)?;
+    let initial_capital = Decimal::try_from(config.initial_capital).unwrap_or(dec!(1000.0));
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+
+    let strategy = strategy_spec(config)?;
+    let grid = sweep::build_grid(&config.get_strategy_config(), &ranges)?;
+    println!(
+        "🔬 Sweep      : {} combinations over {} snapshots ({} threads, rank by {:?})",
+        grid.len(),
+        records.len(),
+        threads,
+        metric
+    );
+
+    let mut results = sweep::run_sweep(&records, &strategy, grid, initial_capital, threads)?;
+    sweep::rank(&mut results, metric);
+
+    println!("🏆 Leaderboard:");
+    for (i, result) in results.iter().take(args.top).enumerate() {
+        println!("{}", sweep::format_result(i + 1, result));
+    }
+    if let Some(best) = results.first() {
+        log_trading_event(&format!("SWEEP BEST | {}", sweep::format_result(1, best)));
+    }
+
+    if let Some(output) = &args.output {
+        sweep::write_leaderboard(output, &results)?;
+        println!("💾 Leaderboard written to {}", output.display());
+    }
+
+    Ok(())
+}
+
+/// Walk-forward optimization: pick params in-sample, trade them out-of-sample, report the stitched OOS result
+fn run_walk_forward(config: &CliConfig, args: &WalkForwardArgs) -> Result<()> {
+    let ranges = args
+        .params
+        .iter()
+        .map(|p| p.parse::<ParamRange>())
+        .collect::<Result<Vec<_>>>()?;
+    let wf_config = WalkForwardConfig {
+        in_sample_markets: args.in_sample,
+        out_of_sample_markets: args.out_of_sample,
+        step_markets: args.step.unwrap_or(args.out_of_sample),
+        metric: args.rank_by.parse()?,
+        min_trades: args.min_trades,
+        min_efficiency: args.min_efficiency,
+        threads: args
+            .threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)),
+    };
+    let records = load_selected_snapshots(config, &args.data)?;
+    let initial_capital = Decimal::try_from(config.initial_capital).unwrap_or(dec!(1000.0));
+
+    let strategy = strategy_spec(config)?;
+    let grid = sweep::build_grid(&config.get_strategy_config(), &ranges)?;
+    println!(
+        "🚶 Walk-forward: {} combinations | IS={} markets | OOS={} markets | step={} | rank by {:?}",
+        grid.len(),
+        wf_config.in_sample_markets,
+        wf_config.out_of_sample_markets,
+        wf_config.step_markets,
+        wf_config.metric
+    );
+
+    let report = walkforward::run_walk_forward(&records, &strategy, &grid, initial_capital, &wf_config)?;
+
+    println!("🪟 Windows:");
+    for window in &report.windows {
+        println!("{}", walkforward::format_window(window));
+    }
+    if report.skipped_windows > 0 {
+        println!(
+            "⚠️  {} window(s) skipped: no parameter set reached {} in-sample trades",
+            report.skipped_windows, wf_config.min_trades
+        );
+    }
+
+    let stats = &report.oos_stats;
+    let summary = format!(
+        "📊 WALK-FORWARD OOS | windows={} | trades={} | win_rate={:.1}% | total_pnl={:.4} | max_dd={:.4} | sharpe={} | efficiency={}",
+        report.windows.len(),
+        stats.total_trades,
+        stats.win_rate(),
+        stats.total_pnl,
+        report.oos_max_drawdown,
+        report.oos_sharpe.map(|s| format!("{:.3}", s)).unwrap_or_else(|| "n/a".to_string()),
+        report.efficiency.map(|e| format!("{:.2}", e)).unwrap_or_else(|| "n/a".to_string())
+    );
+    println!("{}", summary);
+    log_trading_event(&summary);
+
+    if report.passed {
+        println!("✅ Out-of-sample results hold up (efficiency >= {:.2})", wf_config.min_efficiency);
+    } else {
+        let msg = format!(
+            "⚠️  OVERFITTING WARNING | out-of-sample PnL is not positive or efficiency is below {:.2}; in-sample results are unlikely to hold live",
+            wf_config.min_efficiency
+        );
+        println!("{}", msg);
+        log_trading_event(&msg);
+    }
+
+    if let Some(output) = &args.output {
+        std::fs::write(output, serde_json::to_string_pretty(&report)?)?;
+        println!("💾 Report written to {}", output.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+// Composite entry conditions - AND/OR/NOT trees over indicator thresholds
+
+use crate::config::{IndexType, StrategyConfig};
+use crate::indicators::{build_indicator_for, Indicator};
+use serde::{Deserialize, Serialize};
+
+/// Entry condition from config.json `trending_index.entry_condition`, evaluated per token.
+///
+/// ```json
+/// { "all": [
+///     { "indicator": "rsi", "above": 70 },
+///     { "indicator": "macd", "rising": true },
+///     { "not": { "indicator": "volatility", "above": 5.0, "lookback": 20 } }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged, try_from = "EntryConditionJson")]
+pub enum EntryCondition {
+    All { all: Vec<EntryCondition> },
+    Any { any: Vec<EntryCondition> },
+    Not { not: Box<EntryCondition> },
+    Indicator(IndicatorCondition),
+}
+
+/// One indicator compared against thresholds (all given comparisons must hold)
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorCondition {
+    pub indicator: IndexType,
+    /// Window for this indicator (default: the strategy `lookback`)
+    pub lookback: Option<usize>,
+    pub above: Option<f64>,
+    pub below: Option<f64>,
+    /// true = value increased since the previous tick, false = decreased
+    pub rising: Option<bool>,
+}
+
+/// Any condition node as written in config.json (validated into `EntryCondition`)
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntryConditionJson {
+    all: Option<Vec<EntryCondition>>,
+    any: Option<Vec<EntryCondition>>,
+    not: Option<Box<EntryCondition>>,
+    indicator: Option<String>,
+    lookback: Option<usize>,
+    above: Option<f64>,
+    below: Option<f64>,
+    rising: Option<bool>,
+}
+
+impl TryFrom<EntryConditionJson> for EntryCondition {
+    type Error = String;
+
+    fn try_from(json: EntryConditionJson) -> Result<Self, String> {
+        let leaf_fields = json.lookback.is_some() || json.above.is_some() || json.below.is_some() || json.rising.is_some();
+        match (json.all, json.any, json.not, json.indicator) {
+            (Some(all), None, None, None) if !leaf_fields => Ok(Self::All { all }),
+            (None, Some(any), None, None) if !leaf_fields => Ok(Self::Any { any }),
+            (None, None, Some(not), None) if !leaf_fields => Ok(Self::Not { not }),
+            (None, None, None, Some(name)) => {
+                let indicator = IndexType::from_mode(&name)
+                    .ok_or_else(|| format!("Unknown indicator '{}' in entry_condition", name))?;
+                if json.above.is_none() && json.below.is_none() && json.rising.is_none() {
+                    return Err(format!("entry_condition for '{}' needs at least one of above, below or rising", name));
+                }
+                if json.lookback == Some(0) {
+                    return Err(format!("entry_condition lookback for '{}' must be at least 1", name));
+                }
+                Ok(Self::Indicator(IndicatorCondition {
+                    indicator,
+                    lookback: json.lookback,
+                    above: json.above,
+                    below: json.below,
+                    rising: json.rising,
+                }))
+            }
+            _ => Err("Each entry_condition node needs exactly one of all, any, not or indicator".to_string()),
+        }
+    }
+}
+
+/// Rolling state of one condition leaf for one token
+pub struct ConditionIndicator {
+    indicator: Box<dyn Indicator>,
+    previous: Option<f64>,
+}
+
+impl ConditionIndicator {
+    pub fn update(&mut self, price: f64) {
+        self.previous = self.indicator.value();
+        self.indicator.update(price);
+    }
+}
+
+impl EntryCondition {
+    /// One indicator per leaf, in the order `evaluate` visits them
+    pub fn build_indicators(&self, cfg: &StrategyConfig) -> Vec<ConditionIndicator> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+            .into_iter()
+            .map(|leaf| ConditionIndicator {
+                indicator: build_indicator_for(leaf.indicator, leaf.lookback.unwrap_or(cfg.lookback), cfg),
+                previous: None,
+            })
+            .collect()
+    }
+
+    fn collect_leaves<'a>(&'a self, leaves: &mut Vec<&'a IndicatorCondition>) {
+        match self {
+            Self::All { all: children } | Self::Any { any: children } => {
+                children.iter().for_each(|c| c.collect_leaves(leaves))
+            }
+            Self::Not { not } => not.collect_leaves(leaves),
+            Self::Indicator(leaf) => leaves.push(leaf),
+        }
+    }
+
+    /// Evaluate against one token's leaf indicators (from `build_indicators`).
+    /// Fails while any leaf is still warming up, so a NOT never passes on missing data.
+    pub fn evaluate(&self, indicators: &[ConditionIndicator]) -> bool {
+        let mut next = 0;
+        self.evaluate_at(indicators, &mut next).unwrap_or(false)
+    }
+
+    /// None while a leaf of this node has no usable value yet
+    fn evaluate_at(&self, indicators: &[ConditionIndicator], next: &mut usize) -> Option<bool> {
+        match self {
+            // Every child is visited (no short-circuit) so leaf positions stay aligned
+            Self::All { all } => {
+                let results: Vec<_> = all.iter().map(|c| c.evaluate_at(indicators, next)).collect();
+                Some(results.into_iter().collect::<Option<Vec<_>>>()?.into_iter().all(|ok| ok))
+            }
+            Self::Any { any } => {
+                let results: Vec<_> = any.iter().map(|c| c.evaluate_at(indicators, next)).collect();
+                Some(results.into_iter().collect::<Option<Vec<_>>>()?.into_iter().any(|ok| ok))
+            }
+            Self::Not { not } => not.evaluate_at(indicators, next).map(|ok| !ok),
+            Self::Indicator(leaf) => {
+                let state = indicators.get(*next);
+                *next += 1;
+                leaf.holds(state?)
+            }
+        }
+    }
+}
+
+impl IndicatorCondition {
+    /// None while the indicator is warming up (or has no previous value for `rising`)
+    fn holds(&self, state: &ConditionIndicator) -> Option<bool> {
+        let value = state.indicator.value().filter(|_| state.indicator.is_ready())?;
+        if self.above.is_some_and(|t| value <= t) || self.below.is_some_and(|t| value >= t) {
+            return Some(false);
+        }
+        Some(match (self.rising, state.previous) {
+            (None, _) => true,
+            (Some(_), None) => return None,
+            (Some(true), Some(previous)) => value > previous,
+            (Some(false), Some(previous)) => value < previous,
+        })
+    }
+}
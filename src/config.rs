@@ -4,8 +4,14 @@ use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
 use serde::{Serialize, Serializer, Deserialize};
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
+use crate::conditions::EntryCondition;
+use crate::indicators::{
+    BollingerPercentB, Ema, Indicator, RateOfChange, RealizedVolatility, RollingMACD, RollingMomentum, RollingRSI, Sma,
+    Stochastic, WilliamsR, ZScore,
+};
 use std::path::PathBuf;
+use std::collections::HashMap;
 use std::fs;
 
 /// Execution mode
@@ -15,6 +21,15 @@ pub enum Mode {
     Live,       // Real-time trading - monitoring and sending real orders
 }
 
+/// What a restarted live trader does with the position in its session journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionRestart {
+    /// Reconcile the position and keep managing it
+    Resume,
+    /// Cancel its orders and sell what is held at the best bid
+    Flatten,
+}
+
 /// Index type for trend detection (RSI, MACD, Momentum, etc.)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum IndexType {
@@ -22,6 +37,177 @@ pub enum IndexType {
     MACD,
     MACDSignal,  // MACD with Signal Line crossover strategy
     Momentum,
+    EMA,                // Exponential moving average of the token price
+    SMA,                // Simple moving average of the token price
+    BollingerPercentB,  // Position within the Bollinger bands (1.0 = upper band)
+    Stochastic,         // Stochastic %K (0-100)
+    WilliamsR,          // Williams %R (-100..0)
+    ROC,                // Rate of change (%)
+    RealizedVolatility, // Std dev of log returns (%)
+    ZScore,             // Price z-score over the lookback window
+}
+
+/// Everything specific to one index type. A new indicator is a variant above plus a row in `INDEX_SPECS`.
+pub struct IndexSpec {
+    pub index_type: IndexType,
+    /// `--strategy` / `trending_index.mode` name first, then accepted aliases
+    pub modes: &'static [&'static str],
+    /// Short name for logs
+    pub label: &'static str,
+    /// Full name (shown in strategy names)
+    pub name: &'static str,
+    /// Decimals used when logging index values
+    pub log_decimals: usize,
+    /// Strategy defaults for trading on this index
+    pub defaults: fn() -> StrategyConfig,
+    /// Indicator over a `lookback` window (MACD periods and band width come from the config)
+    pub build: fn(usize, &StrategyConfig) -> Box<dyn Indicator>,
+}
+
+/// One row per `IndexType`
+pub static INDEX_SPECS: [IndexSpec; 12] = [
+    IndexSpec {
+        index_type: IndexType::RSI,
+        modes: &["rsi"],
+        label: "RSI",
+        name: "RSI",
+        log_decimals: 2,
+        defaults: StrategyConfig::default_rsi,
+        build: |lookback, _| Box::new(RollingRSI::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::MACD,
+        modes: &["macd"],
+        label: "MACD",
+        name: "MACD",
+        log_decimals: 4,
+        defaults: StrategyConfig::default_macd,
+        build: |_, cfg| Box::new(RollingMACD::new(cfg.macd_fast_period, cfg.macd_slow_period)),
+    },
+    IndexSpec {
+        index_type: IndexType::MACDSignal,
+        modes: &["macd_signal"],
+        label: "MACD_SIG",
+        name: "MACD Signal",
+        log_decimals: 4,
+        defaults: StrategyConfig::default_macd_signal,
+        build: |_, cfg| Box::new(RollingMACD::new_with_signal(cfg.macd_fast_period, cfg.macd_slow_period, cfg.macd_signal_period)),
+    },
+    IndexSpec {
+        index_type: IndexType::Momentum,
+        modes: &["momentum"],
+        label: "MOM",
+        name: "Momentum",
+        log_decimals: 2,
+        defaults: StrategyConfig::default_momentum,
+        build: |lookback, _| Box::new(RollingMomentum::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::EMA,
+        modes: &["ema"],
+        label: "EMA",
+        name: "EMA",
+        log_decimals: 4,
+        defaults: || StrategyConfig::with_index(IndexType::EMA, 0.6, 10),
+        build: |lookback, _| Box::new(Ema::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::SMA,
+        modes: &["sma"],
+        label: "SMA",
+        name: "SMA",
+        log_decimals: 4,
+        defaults: || StrategyConfig::with_index(IndexType::SMA, 0.6, 10),
+        build: |lookback, _| Box::new(Sma::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::BollingerPercentB,
+        modes: &["bollinger", "bollinger_b"],
+        label: "BB%B",
+        name: "Bollinger %B",
+        log_decimals: 4,
+        defaults: || StrategyConfig::with_index(IndexType::BollingerPercentB, 1.0, 20),
+        build: |lookback, cfg| Box::new(BollingerPercentB::new(lookback, cfg.band_std_dev)),
+    },
+    IndexSpec {
+        index_type: IndexType::Stochastic,
+        modes: &["stochastic"],
+        label: "STOCH",
+        name: "Stochastic",
+        log_decimals: 2,
+        defaults: || StrategyConfig::with_index(IndexType::Stochastic, 80.0, 14),
+        build: |lookback, _| Box::new(Stochastic::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::WilliamsR,
+        modes: &["williams_r"],
+        label: "W%R",
+        name: "Williams %R",
+        log_decimals: 2,
+        defaults: || StrategyConfig::with_index(IndexType::WilliamsR, -20.0, 14),
+        build: |lookback, _| Box::new(WilliamsR::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::ROC,
+        modes: &["roc"],
+        label: "ROC",
+        name: "ROC",
+        log_decimals: 2,
+        defaults: || StrategyConfig::with_index(IndexType::ROC, 2.0, 10),
+        build: |lookback, _| Box::new(RateOfChange::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::RealizedVolatility,
+        modes: &["volatility", "realized_volatility"],
+        label: "VOL",
+        name: "Realized Volatility",
+        log_decimals: 4,
+        defaults: || StrategyConfig::with_index(IndexType::RealizedVolatility, 2.0, 20),
+        build: |lookback, _| Box::new(RealizedVolatility::new(lookback)),
+    },
+    IndexSpec {
+        index_type: IndexType::ZScore,
+        modes: &["zscore", "z_score"],
+        label: "Z",
+        name: "Z-Score",
+        log_decimals: 2,
+        defaults: || StrategyConfig::with_index(IndexType::ZScore, 2.0, 20),
+        build: |lookback, _| Box::new(ZScore::new(lookback)),
+    },
+];
+
+impl IndexType {
+    /// This type's row in `INDEX_SPECS`
+    pub fn spec(self) -> &'static IndexSpec {
+        INDEX_SPECS
+            .iter()
+            .find(|spec| spec.index_type == self)
+            .expect("INDEX_SPECS has a row for every IndexType")
+    }
+
+    /// Canonical `--strategy` / `trending_index.mode` names
+    pub fn modes() -> impl Iterator<Item = &'static str> {
+        INDEX_SPECS.iter().map(|spec| spec.modes[0])
+    }
+
+    /// Parse a `--strategy` / `trending_index.mode` name
+    pub fn from_mode(mode: &str) -> Option<Self> {
+        let mode = mode.to_lowercase();
+        INDEX_SPECS
+            .iter()
+            .find(|spec| spec.modes.contains(&mode.as_str()))
+            .map(|spec| spec.index_type)
+    }
+
+    /// Short name for logs
+    pub fn label(&self) -> &'static str {
+        self.spec().label
+    }
+
+    /// Decimals used when logging index values
+    pub fn log_decimals(&self) -> usize {
+        self.spec().log_decimals
+    }
 }
 
 /// Strategy configuration
@@ -42,6 +228,11 @@ pub struct StrategyConfig {
     pub momentum_threshold_pct: f64,
     pub use_macd_sl_filter: bool,
     pub trading_start_when_remaining_minutes: Option<u64>,
+    pub band_std_dev: f64,  // Bollinger band width in standard deviations (default: 2.0)
+    /// Composite entry condition; replaces the single index threshold check when set
+    pub entry_condition: Option<EntryCondition>,
+    /// Merge matched Up+Down pairs back to USDC as soon as they are held (instead of at resolution)
+    pub merge_hedged_pairs: bool,
 }
 
 /// Helper function to serialize Decimal as f64
@@ -67,6 +258,9 @@ impl StrategyConfig {
             momentum_threshold_pct: 2.0,
             use_macd_sl_filter: false,
             trading_start_when_remaining_minutes: None,
+            band_std_dev: 2.0,
+            entry_condition: None,
+            merge_hedged_pairs: false,
         }
     }
 
@@ -84,6 +278,9 @@ impl StrategyConfig {
             momentum_threshold_pct: 2.0,
             use_macd_sl_filter: true,
             trading_start_when_remaining_minutes: None,
+            band_std_dev: 2.0,
+            entry_condition: None,
+            merge_hedged_pairs: false,
         }
     }
 
@@ -101,6 +298,9 @@ impl StrategyConfig {
             momentum_threshold_pct: 2.0,
             use_macd_sl_filter: false,
             trading_start_when_remaining_minutes: None,
+            band_std_dev: 2.0,
+            entry_condition: None,
+            merge_hedged_pairs: false,
         }
     }
 
@@ -118,6 +318,24 @@ impl StrategyConfig {
             momentum_threshold_pct: 2.0,
             use_macd_sl_filter: false,
             trading_start_when_remaining_minutes: None,
+            band_std_dev: 2.0,
+            entry_condition: None,
+            merge_hedged_pairs: false,
+        }
+    }
+
+    /// Defaults for any index type: `trend_threshold` is the level the index must exceed to enter
+    pub fn default_for(index_type: IndexType) -> Self {
+        (index_type.spec().defaults)()
+    }
+
+    /// Momentum defaults with another index, its entry threshold and lookback
+    fn with_index(index_type: IndexType, trend_threshold: f64, lookback: usize) -> Self {
+        Self {
+            trend_threshold,
+            lookback,
+            index_type,
+            ..Self::default_momentum()
         }
     }
 }
@@ -127,7 +345,8 @@ impl StrategyConfig {
 #[command(name = "trending-index-trader")]
 #[command(about = "Real-time trading bot using trending index strategies")]
 pub struct CliConfig {
-    /// Strategy type (rsi, macd, momentum)
+    /// Strategy name from the registry, or an index mode for the momentum hedge strategy
+    /// (rsi, macd, macd_signal, momentum, ema, sma, bollinger, stochastic, williams_r, roc, volatility, zscore)
     #[arg(long, default_value = "rsi")]
     pub strategy: String,
 
@@ -147,11 +366,15 @@ pub struct CliConfig {
     #[arg(long)]
     pub lookback: Option<usize>,
 
+    /// Only enter once the market has at most this many minutes left
+    #[arg(long)]
+    pub trading_start_minutes: Option<u64>,
+
     /// Position size in shares
     #[arg(long, default_value = "10.0")]
     pub position_size: f64,
 
-    /// Market to trade (eth, btc, solana, xrp, or all)
+    /// Market to trade (asset symbol or alias from the registry, comma-separated list, or all)
     #[arg(long, default_value = "all")]
     pub market: String,
 
@@ -190,15 +413,208 @@ pub struct CliConfig {
     /// Configuration file path (JSON format)
     #[arg(long, default_value = "config.json")]
     pub config: PathBuf,
+
+    /// Offline command (runs the live/simulation bot when omitted)
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Offline subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Replay recorded price points through the strategy and report trading stats
+    Backtest(BacktestArgs),
+    /// Drive the simulation trader from recorded snapshots instead of the live market
+    Replay(ReplayArgs),
+    /// Grid-search StrategyConfig parameters with parallel backtests and rank the results
+    Sweep(SweepArgs),
+    /// Optimize on rolling in-sample windows and validate each choice out-of-sample
+    WalkForward(WalkForwardArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WalkForwardArgs {
+    /// Price data file or recording (same formats as `backtest --data`)
+    #[arg(long)]
+    pub data: PathBuf,
+
+    /// Parameter range, repeatable: name=start:end:step (inclusive) or name=v1,v2,...
+    #[arg(long = "param", required = true)]
+    pub params: Vec<String>,
+
+    /// In-sample window length in 15-minute markets
+    #[arg(long, default_value = "96")]
+    pub in_sample: usize,
+
+    /// Out-of-sample window length in 15-minute markets
+    #[arg(long, default_value = "32")]
+    pub out_of_sample: usize,
+
+    /// Markets to advance between windows (defaults to --out-of-sample)
+    #[arg(long)]
+    pub step: Option<usize>,
+
+    /// Ranking metric used to pick the in-sample winner: pnl, win_rate, drawdown or sharpe
+    #[arg(long, default_value = "pnl")]
+    pub rank_by: String,
+
+    /// Minimum in-sample trades for a parameter set to be eligible
+    #[arg(long, default_value = "5")]
+    pub min_trades: usize,
+
+    /// Minimum walk-forward efficiency (OOS vs IS PnL per market) to pass the overfitting check
+    #[arg(long, default_value = "0.5")]
+    pub min_efficiency: f64,
+
+    /// Worker threads (defaults to the number of CPU cores)
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Write the full report (windows + stitched OOS stats) to this JSON file
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SweepArgs {
+    /// Price data file or recording (same formats as `backtest --data`)
+    #[arg(long)]
+    pub data: PathBuf,
+
+    /// Parameter range, repeatable: name=start:end:step (inclusive) or name=v1,v2,...
+    #[arg(long = "param", required = true)]
+    pub params: Vec<String>,
+
+    /// Ranking metric: pnl, win_rate, drawdown or sharpe
+    #[arg(long, default_value = "pnl")]
+    pub rank_by: String,
+
+    /// Number of top results to print
+    #[arg(long, default_value = "10")]
+    pub top: usize,
+
+    /// Worker threads (defaults to the number of CPU cores)
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Write the full leaderboard to this path (.json for JSON, otherwise CSV)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReplayArgs {
+    /// Snapshot recording file or directory (written by the recorder)
+    #[arg(long)]
+    pub data: PathBuf,
+
+    /// Playback speed: 1x (real time), Nx, or max
+    #[arg(long, default_value = "max")]
+    pub speed: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BacktestArgs {
+    /// Price data file: CSV with a header row, or JSONL with one price point per line
+    #[arg(long)]
+    pub data: PathBuf,
+
+    /// Write the full report (stats + trade log) as JSON to this path
+    #[arg(long)]
+    pub output: Option<PathBuf>,
 }
 
 /// JSON configuration file structure
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct JsonConfig {
     pub polymarket: Option<PolymarketConfig>,
     pub trading: Option<TradingConfigJson>,
     #[serde(rename = "trending_index")]
     pub trending_index: Option<TrendingIndexJson>,
+    /// Tradable assets (replaces the built-in ETH/BTC/SOL/XRP list when present)
+    #[serde(rename = "assets")]
+    pub assets: Option<Vec<AssetConfig>>,
+    /// CLOB market channel streaming (prices fall back to REST polling when disabled/down)
+    #[serde(rename = "websocket")]
+    pub websocket: Option<WebSocketConfigJson>,
+    /// Snapshot recording for later replay/backtests
+    #[serde(rename = "recording")]
+    pub recording: Option<RecordingConfigJson>,
+    /// Live session journal for crash recovery
+    #[serde(rename = "session")]
+    pub session: Option<SessionConfigJson>,
+    /// Per-strategy settings, keyed by `--strategy` name (passed to the strategy's factory)
+    #[serde(rename = "strategies")]
+    pub strategies: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordingConfigJson {
+    #[serde(rename = "enabled")]
+    pub enabled: Option<bool>,
+    /// Directory for the per-day `snapshots-YYYY-MM-DD.jsonl` files
+    #[serde(rename = "directory")]
+    pub directory: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionConfigJson {
+    #[serde(rename = "enabled")]
+    pub enabled: Option<bool>,
+    /// Journal file, rewritten on every position/order change
+    #[serde(rename = "journal_path")]
+    pub journal_path: Option<String>,
+    /// What a restart does with a journaled position: "resume" (default) or "flatten"
+    #[serde(rename = "on_restart")]
+    pub on_restart: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebSocketConfigJson {
+    #[serde(rename = "enabled")]
+    pub enabled: Option<bool>,
+    #[serde(rename = "market_url")]
+    pub market_url: Option<String>,
+    /// Minimum time between feed-driven strategy evaluations (coalesces bursts of updates)
+    #[serde(rename = "min_update_interval_ms")]
+    pub min_update_interval_ms: Option<u64>,
+}
+
+/// One tradable asset: its symbol, how to find its 15-minute market, and whether it is enabled
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetConfig {
+    /// Canonical symbol used in logs and `--market` (e.g. "ETH")
+    #[serde(rename = "symbol")]
+    pub symbol: String,
+    /// Extra names accepted by `--market` (e.g. ["ethereum"])
+    #[serde(rename = "aliases", default)]
+    pub aliases: Vec<String>,
+    /// Slug prefixes tried in order when discovering the market (e.g. ["sol", "solana"])
+    #[serde(rename = "slug_prefixes")]
+    pub slug_prefixes: Vec<String>,
+    #[serde(rename = "enabled", default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl AssetConfig {
+    pub fn new(symbol: &str, aliases: &[&str], slug_prefixes: &[&str], enabled: bool) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            slug_prefixes: slug_prefixes.iter().map(|p| p.to_string()).collect(),
+            enabled,
+        }
+    }
+
+    /// Whether `name` refers to this asset (symbol or alias, case-insensitive)
+    pub fn matches(&self, name: &str) -> bool {
+        self.symbol.eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -219,6 +635,8 @@ pub struct PolymarketConfig {
     pub proxy_wallet_address: Option<String>,
     #[serde(rename = "signature_type")]
     pub signature_type: Option<u8>,
+    #[serde(rename = "rpc_url")]
+    pub rpc_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -241,12 +659,22 @@ pub struct TradingConfigJson {
     /// For example, if set to 10, trading starts when 10 minutes or less remain in the market
     #[serde(rename = "trading_start_when_remaining_minutes")]
     pub trading_start_when_remaining_minutes: Option<u64>,
+    /// Order book levels kept per side for depth-aware sizing (default 10)
+    #[serde(rename = "book_depth")]
+    pub book_depth: Option<usize>,
+    /// Merge matched Up+Down pairs back to USDC right away (default false: hold to resolution)
+    #[serde(rename = "merge_hedged_pairs")]
+    pub merge_hedged_pairs: Option<bool>,
+    /// Seconds between live reconciliations against the CLOB (0 = off)
+    #[serde(rename = "reconcile_interval_secs")]
+    pub reconcile_interval_secs: Option<u64>,
 }
 
 /// Trending index configuration (strategy + threshold) from config.json
 #[derive(Debug, Clone, Deserialize)]
 pub struct TrendingIndexJson {
-    /// Mode: "rsi", "macd", "momentum"
+    /// Mode: "rsi", "macd", "macd_signal", "momentum", "ema", "sma", "bollinger", "stochastic",
+    /// "williams_r", "roc", "volatility", "zscore"
     #[serde(rename = "mode")]
     pub mode: Option<String>,
     /// Threshold, e.g. 70 for RSI
@@ -267,6 +695,12 @@ pub struct TrendingIndexJson {
     /// Use MACD filter for stop loss (only trigger SL if MACD <= 0)
     #[serde(rename = "use_macd_sl_filter")]
     pub use_macd_sl_filter: Option<bool>,
+    /// Bollinger band width in standard deviations (default: 2.0)
+    #[serde(rename = "band_std_dev")]
+    pub band_std_dev: Option<f64>,
+    /// AND/OR/NOT tree of indicator thresholds gating entries (see CONFIG.md)
+    #[serde(rename = "entry_condition")]
+    pub entry_condition: Option<EntryCondition>,
 }
 
 impl CliConfig {
@@ -275,10 +709,10 @@ impl CliConfig {
         let config_path = &self.config;
         
         if !config_path.exists() {
-            return Ok(JsonConfig { polymarket: None, trading: None, trending_index: None });
+            return Ok(JsonConfig::default());
         }
 
-        let content = fs::read_to_string(&config_path)
+        let content = fs::read_to_string(config_path)
             .map_err(|e| format!("Failed to read config file {}: {}", config_path.display(), e))?;
         
         let json_config: JsonConfig = serde_json::from_str(&content)
@@ -328,6 +762,14 @@ impl CliConfig {
             .or_else(|| std::env::var("POLYMARKET_PROXY_WALLET_ADDRESS").ok())
     }
 
+    /// Get the Polygon RPC URL from config file or environment variable (default: public Polygon RPC)
+    pub fn get_rpc_url(&self) -> String {
+        self.load_json_config().ok()
+            .and_then(|cfg| cfg.polymarket?.rpc_url)
+            .or_else(|| std::env::var("POLYGON_RPC_URL").ok())
+            .unwrap_or_else(|| "https://polygon-rpc.com".to_string())
+    }
+
     /// Get signature type from config file (defaults to 0 = EOA)
     pub fn get_signature_type(&self) -> Option<u8> {
         self.load_json_config().ok()
@@ -367,6 +809,76 @@ impl CliConfig {
             .unwrap_or(5000)
     }
 
+    /// Order book levels per side exposed on each quote (default 10)
+    pub fn get_book_depth(&self) -> usize {
+        self.load_json_config()
+            .ok()
+            .and_then(|cfg| cfg.trading?.book_depth)
+            .unwrap_or(crate::monitor::DEFAULT_BOOK_DEPTH)
+    }
+
+    /// Snapshot recording directory, or None when recording is off (default off)
+    pub fn get_recording_dir(&self) -> Option<PathBuf> {
+        let recording = self.load_json_config().ok()?.recording?;
+        if !recording.enabled.unwrap_or(false) {
+            return None;
+        }
+        Some(PathBuf::from(recording.directory.unwrap_or_else(|| "recordings".to_string())))
+    }
+
+    /// Seconds between live reconciliations against CLOB open orders, trades and balances (default 30, 0 = off)
+    pub fn get_reconcile_interval_secs(&self) -> u64 {
+        self.load_json_config()
+            .ok()
+            .and_then(|cfg| cfg.trading?.reconcile_interval_secs)
+            .unwrap_or(30)
+    }
+
+    /// Live session journal file, or None when journaling is off (default on, `session.json`)
+    pub fn get_session_journal_path(&self) -> Option<PathBuf> {
+        let session = self.load_json_config().ok().and_then(|cfg| cfg.session);
+        if !session.as_ref().and_then(|s| s.enabled).unwrap_or(true) {
+            return None;
+        }
+        Some(PathBuf::from(
+            session.and_then(|s| s.journal_path).unwrap_or_else(|| "session.json".to_string()),
+        ))
+    }
+
+    /// Whether a restart closes out the journaled position instead of resuming it (`session.on_restart`)
+    pub fn get_session_on_restart(&self) -> anyhow::Result<SessionRestart> {
+        let mode = self.load_json_config().ok().and_then(|cfg| cfg.session?.on_restart);
+        match mode.as_deref() {
+            None | Some("resume") => Ok(SessionRestart::Resume),
+            Some("flatten") => Ok(SessionRestart::Flatten),
+            Some(other) => anyhow::bail!("Invalid session.on_restart '{}': expected \"resume\" or \"flatten\"", other),
+        }
+    }
+
+    /// Whether to stream prices from the CLOB market channel (default true)
+    pub fn is_websocket_enabled(&self) -> bool {
+        self.load_json_config()
+            .ok()
+            .and_then(|cfg| cfg.websocket?.enabled)
+            .unwrap_or(true)
+    }
+
+    /// CLOB market channel WebSocket URL
+    pub fn get_market_ws_url(&self) -> String {
+        self.load_json_config()
+            .ok()
+            .and_then(|cfg| cfg.websocket?.market_url)
+            .unwrap_or_else(|| crate::feed::DEFAULT_MARKET_WS_URL.to_string())
+    }
+
+    /// Minimum spacing between feed-driven ticks in milliseconds (default 250ms)
+    pub fn get_min_update_interval_ms(&self) -> u64 {
+        self.load_json_config()
+            .ok()
+            .and_then(|cfg| cfg.websocket?.min_update_interval_ms)
+            .unwrap_or(250)
+    }
+
     /// Whether ETH trading is enabled (default true)
     pub fn is_eth_enabled(&self) -> bool {
         self.load_json_config()
@@ -391,6 +903,65 @@ impl CliConfig {
             .unwrap_or(true)
     }
 
+    /// Asset registry: `assets` from config.json if present, otherwise the built-in
+    /// ETH/BTC/SOL/XRP set with the legacy enable_* flags applied.
+    pub fn get_asset_configs(&self) -> Vec<AssetConfig> {
+        if let Some(assets) = self.load_json_config().ok().and_then(|cfg| cfg.assets) {
+            return assets;
+        }
+        vec![
+            // BTC always allowed (there's no enable_btc_trading in the JSON)
+            AssetConfig::new("BTC", &["bitcoin"], &["btc"], true),
+            AssetConfig::new("ETH", &["ethereum"], &["eth"], self.is_eth_enabled()),
+            AssetConfig::new("SOL", &["solana"], &["sol", "solana"], self.is_solana_enabled()),
+            AssetConfig::new("XRP", &[], &["xrp"], self.is_xrp_enabled()),
+        ]
+    }
+
+    /// Symbols to trade: CLI `--market` (symbol, alias, comma-separated list, or "all")
+    /// resolved against the enabled assets of the registry.
+    pub fn get_trading_assets(&self) -> Vec<String> {
+        let enabled: Vec<AssetConfig> = self
+            .get_asset_configs()
+            .into_iter()
+            .filter(|a| a.enabled)
+            .collect();
+
+        let market = self.market.trim();
+        if market.eq_ignore_ascii_case("all") {
+            return enabled.into_iter().map(|a| a.symbol).collect();
+        }
+
+        let selected: Vec<String> = market
+            .split(',')
+            .filter_map(|name| enabled.iter().find(|a| a.matches(name.trim())))
+            .map(|a| a.symbol.clone())
+            .collect();
+
+        if selected.is_empty() {
+            // Unknown market name: fall back to every enabled asset
+            enabled.into_iter().map(|a| a.symbol).collect()
+        } else {
+            selected
+        }
+    }
+
+    /// Whether an asset name from recorded data is selected by `--market`
+    /// ("all", or a symbol/alias/comma-separated list; unknown names match literally)
+    pub fn market_selects(&self, asset: &str) -> bool {
+        let market = self.market.trim();
+        if market.eq_ignore_ascii_case("all") {
+            return true;
+        }
+        let assets = self.get_asset_configs();
+        market.split(',').map(|name| name.trim()).any(|name| {
+            match assets.iter().find(|a| a.matches(name)) {
+                Some(config) => config.matches(asset),
+                None => name.eq_ignore_ascii_case(asset),
+            }
+        })
+    }
+
     /// Get execution mode
     pub fn mode(&self) -> Mode {
         if self.live {
@@ -400,6 +971,31 @@ impl CliConfig {
         }
     }
 
+    /// Effective strategy name (registry key):
+    /// 1) CLI --strategy if it is not the default "rsi"
+    /// 2) Else, config.json.trending_index.mode if set
+    /// 3) Else, "rsi"
+    pub fn get_strategy_name(&self) -> String {
+        let cli_strategy = self.strategy.to_lowercase();
+        if cli_strategy != "rsi" {
+            return cli_strategy;
+        }
+        self.load_json_config()
+            .ok()
+            .and_then(|cfg| cfg.trending_index)
+            .and_then(|ti| ti.mode)
+            .map(|s| s.to_lowercase())
+            .unwrap_or(cli_strategy)
+    }
+
+    /// The strategy's own section from config.json `strategies.<name>`
+    pub fn get_strategy_section(&self, name: &str) -> Option<serde_json::Value> {
+        self.load_json_config()
+            .ok()?
+            .strategies?
+            .remove(name)
+    }
+
     /// Get strategy configuration
     pub fn get_strategy_config(&self) -> StrategyConfig {
         // Load JSON once so we can use it for multiple fields
@@ -408,29 +1004,18 @@ impl CliConfig {
             .as_ref()
             .and_then(|cfg| cfg.trading.as_ref());
 
-        // Determine effective strategy name:
-        // 1) If CLI --strategy is not the default "rsi", use it.
-        // 2) Else, if config.json.trending_index.mode is set, use that.
-        // 3) Else, default to "rsi".
-        let cli_strategy = self.strategy.to_lowercase();
-        let strategy_name = if cli_strategy != "rsi" {
-            cli_strategy
-        } else {
-            json_cfg
-                .as_ref()
-                .and_then(|cfg| cfg.trending_index.as_ref())
-                .and_then(|ti| ti.mode.as_ref())
-                .map(|s| s.to_lowercase())
-                .unwrap_or(cli_strategy)
-        };
-
-        let mut config = match strategy_name.as_str() {
-            "rsi" => StrategyConfig::default_rsi(),
-            "macd" => StrategyConfig::default_macd(),
-            "macd_signal" => StrategyConfig::default_macd_signal(),
-            "momentum" => StrategyConfig::default_momentum(),
-            _ => StrategyConfig::default_rsi(),
-        };
+        // Index type: the strategy name if it is an index mode, else trending_index.mode, else RSI
+        let strategy_name = self.get_strategy_name();
+        let mut config = IndexType::from_mode(&strategy_name)
+            .or_else(|| {
+                json_cfg
+                    .as_ref()
+                    .and_then(|cfg| cfg.trending_index.as_ref())
+                    .and_then(|ti| ti.mode.as_deref())
+                    .and_then(IndexType::from_mode)
+            })
+            .map(StrategyConfig::default_for)
+            .unwrap_or_else(StrategyConfig::default_rsi);
 
         // Trend threshold:
         // 1) CLI --trend-threshold if provided
@@ -539,16 +1124,44 @@ impl CliConfig {
             config.use_macd_sl_filter = use_filter;
         }
 
-        // Trading start delay:
-        // config.json.trading.trading_start_when_remaining_minutes if provided
-        if let Some(remaining_minutes) = json_cfg
+        // Bollinger band width:
+        // config.json.trending_index.band_std_dev if provided
+        if let Some(band_std_dev) = json_cfg
+            .as_ref()
+            .and_then(|cfg| cfg.trending_index.as_ref())
+            .and_then(|ti| ti.band_std_dev)
+        {
+            config.band_std_dev = band_std_dev;
+        }
+
+        // Composite entry condition:
+        // config.json.trending_index.entry_condition if provided
+        if let Some(condition) = json_cfg
             .as_ref()
-            .and_then(|cfg| cfg.trading.as_ref())
-            .and_then(|t| t.trading_start_when_remaining_minutes)
+            .and_then(|cfg| cfg.trending_index.as_ref())
+            .and_then(|ti| ti.entry_condition.clone())
         {
+            config.entry_condition = Some(condition);
+        }
+
+        // Trading start delay:
+        // 1) CLI --trading-start-minutes if provided
+        // 2) config.json.trading.trading_start_when_remaining_minutes if provided
+        if let Some(remaining_minutes) = self.trading_start_minutes.or_else(|| {
+            json_cfg
+                .as_ref()
+                .and_then(|cfg| cfg.trading.as_ref())
+                .and_then(|t| t.trading_start_when_remaining_minutes)
+        }) {
             config.trading_start_when_remaining_minutes = Some(remaining_minutes);
         }
 
+        // Hedged pair merging:
+        // config.json.trading.merge_hedged_pairs if provided
+        if let Some(merge) = trading_cfg.and_then(|t| t.merge_hedged_pairs) {
+            config.merge_hedged_pairs = merge;
+        }
+
         config
     }
 
@@ -557,6 +1170,8 @@ impl CliConfig {
         if self.mode() == Mode::Live && self.get_private_key().is_none() {
             return Err("Private key required for live trading mode. Set POLYMARKET_PRIVATE_KEY environment variable or use --private-key".to_string());
         }
+        // Strategy settings such as entry_condition must not be silently dropped on a parse error
+        self.load_json_config()?;
         Ok(())
     }
 }
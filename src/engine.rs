@@ -0,0 +1,1783 @@
+// Trading engine - strategy, indicators and TP/SL cycle logic shared by simulation and live trading
+//
+// The engine decides *what* to trade; an `Executor` decides *how* orders are placed and
+// when they count as filled (instantly on paper, from each order's fills on the CLOB).
+
+use crate::config::CliConfig;
+use crate::indicators::MarketIndicators;
+use crate::models::{MarketData, TokenPrice};
+use crate::monitor::{MarketDataSource, MarketSnapshot};
+use crate::session::{AssetSession, JournaledEntry, JournaledOrders, SessionJournal, SessionState, SESSION_FORMAT_VERSION};
+use crate::strategies::{Quote, Quotes, Strategy, TradeAction};
+use crate::types::{ActiveCycle, PositionSide, PositionState, PricePoint, TradeLog, TradingStats, MARKET_DURATION_SECS};
+use crate::log_event as log;
+use anyhow::Result;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+/// Unfilled entry orders are cancelled after this long
+const ENTRY_TIMEOUT_SECS: u64 = 10;
+/// Taker orders (stop losses, hedged-entry legs, unwinds) still unfilled after this long have their rest cancelled
+const TAKER_TIMEOUT_SECS: u64 = 5;
+/// Entries above this price are skipped until the last two minutes of the market
+const LATE_ENTRY_PRICE: Decimal = dec!(0.93);
+const LATE_ENTRY_ELAPSED_SECS: u64 = 13 * 60;
+/// A cycle with fewer shares left than this is fully exited
+const DUST_SHARES: Decimal = dec!(0.01);
+
+/// Fill state of an entry order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillStatus {
+    /// Resting on the book, or the fill is not confirmed yet
+    Pending,
+    /// Filled for `size` shares, at an average of `price` when the venue reports it (else at the limit)
+    Filled { size: Decimal, price: Option<Decimal> },
+    /// Cancelled or rejected without a fill
+    Cancelled,
+}
+
+/// New fills of a resting exit order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitFill {
+    pub shares: Decimal,
+    /// Average price of these shares
+    pub price: Decimal,
+}
+
+/// State of an open cycle's TP for one tick
+#[derive(Debug, Clone, Copy)]
+enum TpStatus {
+    /// No TP order is resting (paper execution, or the order never went out)
+    NotResting,
+    /// The resting TP sold these shares since the last poll (possibly none)
+    Fills(ExitFill),
+    /// The TP order could not be checked this tick
+    Unknown,
+}
+
+/// Why an open cycle was closed before market end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+}
+
+/// A difference between the engine's state and the venue, found by the reconciler
+#[derive(Debug, Clone)]
+pub enum Discrepancy {
+    /// An open order we don't track; it was cancelled
+    OrphanOrder { order_id: String, token: &'static str, side: String, price: Decimal, size: Decimal },
+    /// Our TP order is gone but its tokens are still held; it was placed again
+    TakeProfitMissing { order_id: String },
+    /// The TP sold `shares` at an average of `price` without the engine seeing the fill; booked, cycle shrunk or closed
+    TakeProfitFilled { order_id: String, shares: Decimal, price: Decimal, pnl: Decimal },
+    /// Fewer tokens held than the open positions account for
+    BalanceShortfall { token: &'static str, expected: Decimal, actual: Decimal },
+    /// More tokens held than the open positions account for; left alone
+    BalanceSurplus { token: &'static str, expected: Decimal, actual: Decimal },
+    /// A fill of an order we never placed in this session
+    UnknownFill { order_id: String, token: &'static str, side: String, price: Decimal, size: Decimal },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OrphanOrder { order_id, token, side, price, size } => write!(
+                f, "ORPHAN ORDER | order_id={} | token={} | side={} | price={:.2} | size={:.2} | cancelled",
+                order_id, token, side, price, size
+            ),
+            Self::TakeProfitMissing { order_id } => write!(
+                f, "TP MISSING | order_id={} | no longer open, tokens still held | placed again", order_id
+            ),
+            Self::TakeProfitFilled { order_id, shares, price, pnl } => write!(
+                f, "TP FILLED | order_id={} | shares={} | price={:.4} | pnl={:.4} | booked", order_id, shares, price, pnl
+            ),
+            Self::BalanceShortfall { token, expected, actual } => write!(
+                f, "BALANCE SHORTFALL | token={} | expected={} | actual={:.6}", token, expected, actual
+            ),
+            Self::BalanceSurplus { token, expected, actual } => write!(
+                f, "BALANCE SURPLUS | token={} | expected={} | actual={:.6} | left alone", token, expected, actual
+            ),
+            Self::UnknownFill { order_id, token, side, price, size } => write!(
+                f, "UNKNOWN FILL | order_id={} | token={} | side={} | price={:.2} | size={:.2}", order_id, token, side, price, size
+            ),
+        }
+    }
+}
+
+/// A marketable order the engine expects to fill at once, resolved over the following ticks
+/// instead of waiting on it (unfilled rests are cancelled after `TAKER_TIMEOUT_SECS`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TakerKind {
+    /// BUY of the opposite token that hedges a stopped-out cycle
+    StopLoss,
+    /// BUY of one token of a hedged (Up+Down) entry
+    HedgeLeg(PositionSide),
+    /// SELL of the shares one hedged-entry leg bought beyond the other
+    Unwind(PositionSide),
+}
+
+/// A limit order the engine wants placed
+#[derive(Debug, Clone)]
+pub struct OrderIntent {
+    pub asset: String,
+    pub token_id: String,
+    /// "UP" or "DOWN" (for logs)
+    pub token: &'static str,
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// Places orders for the engine and reports entry fills.
+///
+/// Executors keep their own per-asset order bookkeeping; the engine only tracks cycles.
+pub trait Executor: Send {
+    /// Log tag ("SIM", "LIVE")
+    fn tag(&self) -> &'static str;
+
+    /// Startup banner line
+    fn banner(&self) -> &'static str;
+
+    /// Delay between an entry fill and placing its TP order
+    fn fill_delay(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Place an entry BUY for the held token
+    fn place_entry(&mut self, order: &OrderIntent) -> impl Future<Output = Result<FillStatus>> + Send;
+
+    /// Check whether the pending entry for `asset` has filled
+    fn poll_entry(&mut self, asset: &str) -> impl Future<Output = Result<FillStatus>> + Send;
+
+    /// Cancel the pending entry for `asset` (timeout or market end) and report what it filled
+    /// before the cancel went through (`Filled` for a partial fill, else `Cancelled`)
+    fn cancel_entry(&mut self, asset: &str) -> impl Future<Output = FillStatus> + Send;
+
+    /// Place the resting TP SELL for a filled entry
+    fn place_take_profit(&mut self, order: &OrderIntent) -> impl Future<Output = Result<()>> + Send;
+
+    /// Whether TP exits only come from the fills of a resting TP order. When false, a TP is
+    /// judged from the price whenever `poll_take_profit` has no order to report on.
+    fn rests_take_profit(&self) -> bool {
+        false
+    }
+
+    /// Shares the resting TP for `asset` sold since the last poll. None when the executor has no
+    /// TP order to report on.
+    fn poll_take_profit(&mut self, _asset: &str) -> impl Future<Output = Result<Option<ExitFill>>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Rest a market-making BUY on one token (the engine cancels the previous quote first)
+    fn place_quote(&mut self, order: &OrderIntent) -> impl Future<Output = Result<()>> + Send;
+
+    /// Shares of the resting quote on `token` filled since the last poll; `book` is that token's latest quote
+    fn poll_quote(&mut self, asset: &str, token: &'static str, book: Option<&TokenPrice>) -> impl Future<Output = Result<Decimal>> + Send;
+
+    /// Cancel the resting quote on `token`
+    fn cancel_quote(&mut self, asset: &str, token: &'static str) -> impl Future<Output = ()> + Send;
+
+    /// Place a taker order; `Filled` if it filled on placement, else `Pending` until `poll_taker` resolves it
+    fn place_taker(&mut self, kind: TakerKind, order: &OrderIntent) -> impl Future<Output = Result<FillStatus>> + Send;
+
+    /// Check the pending taker order of `kind` for `asset`: `Filled` once it filled completely (or
+    /// ended with a partial fill), `Cancelled` if it ended without one, else `Pending`
+    fn poll_taker(&mut self, asset: &str, kind: TakerKind) -> impl Future<Output = Result<FillStatus>> + Send;
+
+    /// Cancel the pending taker order of `kind` for `asset` and report what it filled before the cancel
+    fn cancel_taker(&mut self, asset: &str, kind: TakerKind) -> impl Future<Output = FillStatus> + Send;
+
+    /// Merge `pairs` Up+Down pairs of the market `condition_id` back into USDC
+    fn merge_pairs(&mut self, asset: &str, condition_id: &str, pairs: Decimal) -> impl Future<Output = Result<()>> + Send;
+
+    /// A cycle closed on TP or SL; clean up the other exit order
+    fn cycle_closed(&mut self, asset: &str, reason: ExitReason) -> impl Future<Output = ()> + Send;
+
+    /// The market for `asset` ended; cancel anything still outstanding
+    fn market_ended(&mut self, asset: &str) -> impl Future<Output = ()> + Send;
+
+    /// Compare `local` (the engine's view of `asset`) with the venue's open orders, fills and
+    /// balances, fix drift (in `local` and on the venue) and report each difference
+    fn reconcile(&mut self, asset: &str, local: &mut AssetSession) -> impl Future<Output = Result<Vec<Discrepancy>>> + Send;
+
+    /// Order bookkeeping for `asset` to write to the session journal
+    fn journal_orders(&self, _asset: &str) -> JournaledOrders {
+        JournaledOrders::default()
+    }
+
+    /// After a restart, reconcile the journaled `session` of `asset` with what the venue reports
+    /// (correcting it in place) and take over its orders. With `flatten`, close it out instead.
+    fn recover(&mut self, asset: &str, session: &mut AssetSession, flatten: bool) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Stop-loss BUY of the opposite token placed but not yet resolved
+#[derive(Debug, Clone)]
+struct PendingStopLoss {
+    /// Limit price (1 - SL)
+    price: Decimal,
+    /// Opposite token's ask when the SL triggered (for logs)
+    opposite_ask: Decimal,
+    /// Source-clock time (UNIX seconds) the order was placed
+    placed_at_secs: u64,
+}
+
+/// Hedged entry whose legs are still filling
+#[derive(Debug, Clone)]
+struct PendingHedge {
+    up: HedgeLeg,
+    down: HedgeLeg,
+    fee_rate: Decimal,
+    /// Source-clock time (UNIX seconds) the legs were placed
+    placed_at_secs: u64,
+}
+
+/// One leg of a pending hedged entry
+#[derive(Debug, Clone, Copy)]
+struct HedgeLeg {
+    limit_price: Decimal,
+    /// Filled shares and their average price, once the leg has resolved
+    fill: Option<(Decimal, Decimal)>,
+}
+
+impl HedgeLeg {
+    fn resolved(&mut self, status: FillStatus) {
+        self.fill = Some(match status {
+            FillStatus::Filled { size, price } => (size, price.unwrap_or(self.limit_price)),
+            _ => (Decimal::ZERO, self.limit_price),
+        });
+    }
+}
+
+/// SELL of an unmatched hedged-entry leg placed but not yet resolved
+#[derive(Debug, Clone)]
+struct PendingUnwind {
+    side: PositionSide,
+    /// Limit price (the best bid when placed)
+    price: Decimal,
+    placed_at_secs: u64,
+}
+
+/// Entry order placed but not yet filled
+#[derive(Debug, Clone)]
+struct PendingEntry {
+    side: PositionSide,
+    limit_price: Decimal,
+    /// Source-clock time (UNIX seconds) the order was placed
+    placed_at_secs: u64,
+}
+
+pub(crate) fn token_label(side: PositionSide) -> &'static str {
+    match side {
+        PositionSide::LongUp => "UP",
+        PositionSide::LongDown => "DOWN",
+        PositionSide::Flat => "",
+    }
+}
+
+/// Indicators, open cycle and per-market stats for one asset, so every market trades independently
+struct AssetState {
+    /// Rolling indicators for the Up and Down tokens
+    indicators: MarketIndicators,
+    /// Current active trading cycle (if any)
+    current_cycle: Option<ActiveCycle>,
+    /// Entry order waiting for a fill
+    pending_entry: Option<PendingEntry>,
+    /// Stop loss waiting for its fills; the cycle stays open until it resolves
+    pending_stop_loss: Option<PendingStopLoss>,
+    /// Hedged entry waiting for its legs to fill
+    pending_hedge: Option<PendingHedge>,
+    /// SELL of an unmatched hedge leg waiting for its fills
+    pending_unwind: Option<PendingUnwind>,
+    /// Up+Down position held to resolution (no TP/SL): a hedged entry, a stop-loss hedge or market-making inventory
+    hedge: PositionState,
+    /// A merge failed this market; matched pairs are held to resolution instead
+    merge_failed: bool,
+    /// Market-making bids resting on the book
+    quotes: Quotes,
+    /// Total PnL for the current market (starts at 0 each new market)
+    total_pnl: Decimal,
+    /// Number of winning trades (TP or market-settlement win)
+    wins: usize,
+    /// Number of losing trades (SL or market-settlement loss)
+    losses: usize,
+    /// Total fund used (accumulates entry_price * size for each filled entry)
+    total_fund_used: Decimal,
+    /// Last price point (used for market-end settlement)
+    last_price_point: Option<PricePoint>,
+    /// Last quotes (with book depth and token IDs)
+    last_market_data: Option<MarketData>,
+    /// Market IDs reloaded from the session journal; journaled until a snapshot fills in `last_market_data`
+    restored_market: Option<MarketIds>,
+}
+
+/// Condition and token IDs of an asset's current market
+#[derive(Debug, Clone, Default)]
+struct MarketIds {
+    condition_id: String,
+    up_token_id: Option<String>,
+    down_token_id: Option<String>,
+}
+
+impl AssetState {
+    fn new(strategy: &dyn Strategy) -> Self {
+        Self {
+            indicators: strategy.new_indicators(),
+            current_cycle: None,
+            pending_entry: None,
+            pending_stop_loss: None,
+            pending_hedge: None,
+            pending_unwind: None,
+            hedge: PositionState::NoPosition,
+            merge_failed: false,
+            quotes: Quotes::default(),
+            total_pnl: Decimal::ZERO,
+            wins: 0,
+            losses: 0,
+            total_fund_used: Decimal::ZERO,
+            last_price_point: None,
+            last_market_data: None,
+            restored_market: None,
+        }
+    }
+
+    /// An entry, stop loss, hedged entry or unwind is still waiting for its fills
+    fn has_pending_orders(&self) -> bool {
+        self.pending_entry.is_some() || self.pending_stop_loss.is_some() || self.pending_hedge.is_some() || self.pending_unwind.is_some()
+    }
+
+    /// Quote (with token ID and depth) of the Up or Down token from the last snapshot
+    fn token(&self, side: PositionSide) -> Option<&TokenPrice> {
+        self.last_market_data.as_ref().and_then(|m| match side {
+            PositionSide::LongUp => m.up_token.as_ref(),
+            PositionSide::LongDown => m.down_token.as_ref(),
+            PositionSide::Flat => None,
+        })
+    }
+
+    /// Order for the Up or Down token of `asset`, if its token ID is known
+    fn order_for(&self, asset: &str, side: PositionSide, price: Decimal, size: Decimal) -> Option<OrderIntent> {
+        self.token(side).map(|t| OrderIntent {
+            asset: asset.to_string(),
+            token_id: t.token_id.clone(),
+            token: token_label(side),
+            price,
+            size,
+        })
+    }
+
+    /// Cap entry size at the ask depth available up to the entry price.
+    /// Unchanged when the last quote carried no depth.
+    fn depth_limited_shares(&self, side: PositionSide, entry_price: Decimal, shares: Decimal) -> Decimal {
+        match self.token(side) {
+            Some(t) => t.fillable_buy(shares, entry_price),
+            None => shares,
+        }
+    }
+
+    fn stats_line(&self) -> String {
+        format!(
+            "pnl={:.4} | wins={} | losses={} | fund={:.4}",
+            self.total_pnl, self.wins, self.losses, self.total_fund_used
+        )
+    }
+}
+
+/// Fills and realized PnL of a run: the trade log and stats a backtest reports
+#[derive(Debug)]
+pub struct TradeLedger {
+    pub stats: TradingStats,
+    pub trades: Vec<TradeLog>,
+    initial_capital: Decimal,
+    /// Index name recorded with each entry
+    index_name: String,
+}
+
+impl TradeLedger {
+    fn new(initial_capital: Decimal, index_name: String) -> Self {
+        Self {
+            stats: TradingStats {
+                current_capital: initial_capital,
+                ..TradingStats::default()
+            },
+            trades: Vec::new(),
+            initial_capital,
+            index_name,
+        }
+    }
+
+    /// A fill that opened or added to a position (its PnL is booked when it closes)
+    fn opened(&mut self, ts: u64, asset: &str, action: &str, price: Decimal, amount: Decimal, index: Option<f64>) {
+        self.trades.push(TradeLog {
+            ts,
+            action: action.to_string(),
+            price,
+            amount,
+            current_capital: self.stats.current_capital,
+            pl: Decimal::ZERO,
+            asset: Some(asset.to_string()),
+            trending_index_name: Some(self.index_name.clone()),
+            trending_index_value: index,
+        });
+    }
+
+    /// A realized PnL (TP, merge or settlement): one closed trade on the equity curve
+    #[allow(clippy::too_many_arguments)]
+    fn closed(&mut self, ts: u64, asset: &str, action: &str, price: Decimal, amount: Decimal, pnl: Decimal, is_win: bool) {
+        self.stats.total_trades += 1;
+        if is_win {
+            self.stats.winning_trades += 1;
+        } else {
+            self.stats.losing_trades += 1;
+        }
+        self.stats.total_pnl += pnl;
+        self.stats.current_capital = self.initial_capital + self.stats.total_pnl;
+        let capital = self.stats.current_capital;
+        self.stats.add_equity_point(ts, capital);
+
+        self.trades.push(TradeLog {
+            ts,
+            action: action.to_string(),
+            price,
+            amount,
+            current_capital: capital,
+            pl: pnl,
+            asset: Some(asset.to_string()),
+            trending_index_name: None,
+            trending_index_value: None,
+        });
+    }
+}
+
+/// Strategy + cycle engine driven by a `MarketDataSource`, placing orders through an `Executor`
+pub struct TradingEngine<S: MarketDataSource, E: Executor> {
+    source: Arc<S>,
+    executor: E,
+    /// Current time (UNIX seconds) from the source's clock (the latest snapshot)
+    now_secs: u64,
+    /// Seconds left in the current market (from the latest snapshot)
+    time_remaining_secs: u64,
+    strategy: Box<dyn Strategy>,
+    capital: Decimal,
+    check_interval_ms: u64,
+    trading_assets: Vec<String>,
+    /// Independent state per traded asset
+    assets: BTreeMap<String, AssetState>,
+    /// Previous period timestamp to detect market rollover
+    previous_period_timestamp: Option<u64>,
+    /// Session journal for crash recovery (live only)
+    journal: Option<SessionJournal>,
+    /// Seconds between reconciliations against the venue (0 = off)
+    reconcile_interval_secs: u64,
+    /// Source-clock time of the last reconciliation
+    last_reconcile_secs: u64,
+    /// Every fill and closed trade of this run
+    ledger: TradeLedger,
+}
+
+impl<S: MarketDataSource, E: Executor> TradingEngine<S, E> {
+    pub fn with_executor(
+        source: Arc<S>,
+        executor: E,
+        strategy: Box<dyn Strategy>,
+        config: CliConfig,
+        initial_capital: Decimal,
+    ) -> Self {
+        // Decide which assets to trade based on CLI `--market` and the asset registry
+        let mut engine = Self::for_assets(source, executor, strategy, config.get_trading_assets(), initial_capital);
+        engine.check_interval_ms = config.get_check_interval_ms();
+        engine.reconcile_interval_secs = config.get_reconcile_interval_secs();
+        engine
+    }
+
+    /// Engine trading `trading_assets`, without reconciliation or a poll interval (backtests)
+    pub fn for_assets(
+        source: Arc<S>,
+        executor: E,
+        strategy: Box<dyn Strategy>,
+        trading_assets: Vec<String>,
+        initial_capital: Decimal,
+    ) -> Self {
+        let assets = trading_assets
+            .iter()
+            .map(|a| (a.clone(), AssetState::new(strategy.as_ref())))
+            .collect();
+        let ledger = TradeLedger::new(initial_capital, format!("{:?}", strategy.config().index_type));
+
+        Self {
+            source,
+            executor,
+            now_secs: 0,
+            time_remaining_secs: 0,
+            strategy,
+            capital: initial_capital,
+            check_interval_ms: 0,
+            trading_assets,
+            assets,
+            previous_period_timestamp: None,
+            journal: None,
+            reconcile_interval_secs: 0,
+            last_reconcile_secs: 0,
+            ledger,
+        }
+    }
+
+    /// Trades and stats booked so far
+    pub fn ledger(&self) -> &TradeLedger {
+        &self.ledger
+    }
+
+    pub fn into_ledger(self) -> TradeLedger {
+        self.ledger
+    }
+
+    /// Journal open positions and orders to `journal` on every change
+    pub fn with_journal(mut self, journal: SessionJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Reload the journaled session of the current market, reconcile it through the executor and
+    /// resume managing it (or flatten it). A journal from an ended market is ignored.
+    pub async fn recover(&mut self, flatten: bool) -> Result<()> {
+        let tag = self.executor.tag();
+        let Some(journal) = self.journal.as_ref() else {
+            return Ok(());
+        };
+        let Some(session) = journal.load()? else {
+            log(&format!("[{}] 📂 NO SESSION | journal={} | starting fresh", tag, journal.path().display()));
+            return Ok(());
+        };
+
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        if now >= session.period_timestamp + MARKET_DURATION_SECS {
+            log(&format!(
+                "[{}] 📂 SESSION EXPIRED | period={} | that market has ended; tokens still held resolve on-chain | starting fresh",
+                tag, session.period_timestamp
+            ));
+            return Ok(());
+        }
+        log(&format!(
+            "[{}] 📂 SESSION FOUND | period={} | assets={} | {}",
+            tag, session.period_timestamp, session.assets.len(), if flatten { "flattening" } else { "resuming" }
+        ));
+
+        for (asset, mut saved) in session.assets {
+            // No market data was ever seen for it, so nothing can be open
+            if saved.condition_id.is_empty() {
+                continue;
+            }
+            if !self.assets.contains_key(&asset) {
+                log(&format!("[{}] ⚠️  SESSION SKIPPED | asset={} | not traded in this run; its orders are left alone", tag, asset));
+                continue;
+            }
+            // Resuming without knowing what is on the book could double up positions
+            self.executor
+                .recover(&asset, &mut saved, flatten)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to reconcile {} session: {}", asset, e))?;
+
+            let Some(state) = self.assets.get_mut(&asset) else {
+                continue;
+            };
+            state.current_cycle = saved.current_cycle;
+            state.pending_entry = saved.pending_entry.map(|p| PendingEntry {
+                side: p.side,
+                limit_price: p.limit_price,
+                placed_at_secs: p.placed_at_secs,
+            });
+            state.hedge = saved.hedge;
+            state.total_pnl = saved.total_pnl;
+            state.wins = saved.wins;
+            state.losses = saved.losses;
+            state.total_fund_used = saved.total_fund_used;
+            // Kept so the journal still names the market if we crash again before the first snapshot
+            state.restored_market = Some(MarketIds {
+                condition_id: saved.condition_id,
+                up_token_id: saved.up_token_id,
+                down_token_id: saved.down_token_id,
+            });
+            log(&format!(
+                "[{}] ♻️  SESSION RESTORED | asset={} | cycle={} | pending_entry={} | hedge_cost={:.4} | {}",
+                tag, asset,
+                state.current_cycle.as_ref().map(|c| format!("{:?} {}@{:.4}", c.side, c.size, c.entry_price)).unwrap_or_else(|| "none".to_string()),
+                state.pending_entry.is_some(), state.hedge.cost(), state.stats_line()
+            ));
+        }
+        self.previous_period_timestamp = Some(session.period_timestamp);
+        self.persist();
+        Ok(())
+    }
+
+    /// The engine's and executor's view of one asset, as journaled and reconciled
+    fn asset_session(&self, asset: &str, s: &AssetState) -> AssetSession {
+        let ids = match s.last_market_data.as_ref() {
+            Some(m) => MarketIds {
+                condition_id: m.condition_id.clone(),
+                up_token_id: m.up_token.as_ref().map(|t| t.token_id.clone()),
+                down_token_id: m.down_token.as_ref().map(|t| t.token_id.clone()),
+            },
+            None => s.restored_market.clone().unwrap_or_default(),
+        };
+        AssetSession {
+            condition_id: ids.condition_id,
+            up_token_id: ids.up_token_id,
+            down_token_id: ids.down_token_id,
+            current_cycle: s.current_cycle.clone(),
+            pending_entry: s.pending_entry.as_ref().map(|p| JournaledEntry {
+                side: p.side,
+                limit_price: p.limit_price,
+                placed_at_secs: p.placed_at_secs,
+            }),
+            hedge: s.hedge.clone(),
+            total_pnl: s.total_pnl,
+            wins: s.wins,
+            losses: s.losses,
+            total_fund_used: s.total_fund_used,
+            orders: self.executor.journal_orders(asset),
+        }
+    }
+
+    /// Diff every asset against the venue through the executor, take over its corrections
+    /// (booking TP fills it found in the ledger) and log each discrepancy
+    async fn reconcile(&mut self) {
+        let tag = self.executor.tag();
+        let assets = self.trading_assets.clone();
+        for asset in &assets {
+            let Some(state) = self.assets.get(asset) else {
+                continue;
+            };
+            // No market yet, or an order still waiting for its fills
+            if state.last_market_data.is_none() || state.has_pending_orders() {
+                continue;
+            }
+            let mut local = self.asset_session(asset, state);
+            let events = match self.executor.reconcile(asset, &mut local).await {
+                Ok(events) => events,
+                Err(e) => {
+                    log(&format!("[{}] ⚠️  RECONCILE FAILED | asset={} | {} (will retry)", tag, asset, e));
+                    continue;
+                }
+            };
+            if events.is_empty() {
+                continue;
+            }
+            if let Some(state) = self.assets.get_mut(asset) {
+                state.current_cycle = local.current_cycle;
+                state.total_pnl = local.total_pnl;
+                state.wins = local.wins;
+                state.losses = local.losses;
+            }
+            for event in events {
+                if let Discrepancy::TakeProfitFilled { shares, price, pnl, .. } = event {
+                    self.ledger.closed(self.now_secs, asset, "TP", price, shares, pnl, pnl > Decimal::ZERO);
+                }
+                let msg = format!("[{}] ⚠️  DISCREPANCY | asset={} | {}", tag, asset, event);
+                log(&msg);
+                warn!("{}", msg);
+            }
+            self.persist();
+        }
+    }
+
+    /// Write the current positions and orders to the session journal (no-op without one)
+    fn persist(&mut self) {
+        let Some(period_timestamp) = self.previous_period_timestamp else {
+            return;
+        };
+        if self.journal.is_none() {
+            return;
+        }
+        let assets = self
+            .assets
+            .iter()
+            .map(|(asset, s)| (asset.clone(), self.asset_session(asset, s)))
+            .collect();
+        let state = SessionState {
+            v: SESSION_FORMAT_VERSION,
+            period_timestamp,
+            assets,
+        };
+        if let Some(journal) = self.journal.as_mut() {
+            if let Err(e) = journal.save(&state) {
+                log(&format!("[{}] ⚠️  SESSION JOURNAL WRITE FAILED | {}", self.executor.tag(), e));
+            }
+        }
+    }
+
+    /// Convert MarketSnapshot to PricePoint
+    fn snapshot_to_price_point(snapshot: &MarketSnapshot, asset: &str) -> Option<PricePoint> {
+        let market_data = snapshot.market_for(asset)?;
+        let ask = |t: &Option<TokenPrice>| t.as_ref().and_then(|t| t.ask_price().to_f64()).unwrap_or(0.0);
+
+        Some(PricePoint {
+            timestamp: snapshot.period_timestamp,
+            up_price: ask(&market_data.up_token),
+            down_price: ask(&market_data.down_token),
+            actual_outcome: snapshot.outcomes.get(asset).copied(),
+            asset: Some(asset.to_string()),
+            news_event: None,
+            time_remaining_secs: Some(snapshot.time_remaining_seconds),
+        })
+    }
+
+    /// Handle market end: cancel a pending entry, settle any open cycle on the final prices,
+    /// log a summary and start the asset's next market from scratch
+    async fn handle_market_end(&mut self, asset: &str) {
+        let tag = self.executor.tag();
+        if let Some(pending) = self.assets.get_mut(asset).and_then(|s| s.pending_entry.take()) {
+            log(&format!("[{}] 🧹 MARKET END | asset={} | cancelling pending entry", tag, asset));
+            if let FillStatus::Filled { size, price } = self.executor.cancel_entry(asset).await {
+                // Filled before the cancel went through: settled with the market below
+                self.start_cycle(asset, &pending, size, price);
+            }
+        }
+        if self.assets.get_mut(asset).and_then(|s| s.pending_stop_loss.take()).is_some() {
+            log(&format!("[{}] 🧹 MARKET END | asset={} | cancelling pending stop loss", tag, asset));
+            if let FillStatus::Filled { size, price } = self.executor.cancel_taker(asset, TakerKind::StopLoss).await {
+                // Filled before the cancel went through: hedged, and settled with the market below
+                self.finish_stop_loss(asset, size, price).await;
+            }
+        }
+        if let Some(mut pending) = self.assets.get_mut(asset).and_then(|s| s.pending_hedge.take()) {
+            log(&format!("[{}] 🧹 MARKET END | asset={} | cancelling pending hedged entry", tag, asset));
+            for (side, leg) in [(PositionSide::LongUp, &mut pending.up), (PositionSide::LongDown, &mut pending.down)] {
+                if leg.fill.is_none() {
+                    leg.resolved(self.executor.cancel_taker(asset, TakerKind::HedgeLeg(side)).await);
+                }
+            }
+            // Whatever filled is settled with the market below; no time is left to unwind
+            self.open_hedge(asset, pending, false).await;
+        }
+        if let Some(pending) = self.assets.get_mut(asset).and_then(|s| s.pending_unwind.take()) {
+            log(&format!("[{}] 🧹 MARKET END | asset={} | cancelling pending unwind", tag, asset));
+            if let FillStatus::Filled { size, price } = self.executor.cancel_taker(asset, TakerKind::Unwind(pending.side)).await {
+                self.book_unwind(asset, &pending, size, price);
+            }
+        }
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+
+        // Determine market outcome: the recorded resolution if the data has one, else the winning token trades at ≈ 1.0
+        let winner = state.last_price_point.as_ref().map(|p| match p.actual_outcome {
+            Some(1) => PositionSide::LongUp,
+            Some(0) => PositionSide::LongDown,
+            _ => PositionSide::winner_from_prices(p.up_price, p.down_price),
+        });
+        let outcome_str = match winner {
+            Some(PositionSide::LongUp) => "UP",
+            Some(PositionSide::LongDown) => "DOWN",
+            _ => "UNKNOWN",
+        };
+
+        if let Some(cycle) = state.current_cycle.take() {
+            if let Some(winner) = winner {
+                let (final_pnl, is_win) = cycle.settle(winner);
+
+                state.total_pnl += final_pnl;
+                if is_win {
+                    state.wins += 1;
+                } else {
+                    state.losses += 1;
+                }
+
+                let (exit_price, action) = if is_win { (Decimal::ONE, "SETTLE_WIN") } else { (Decimal::ZERO, "SETTLE_LOSS") };
+                self.ledger.closed(self.now_secs, asset, action, exit_price, cycle.size, final_pnl, is_win);
+
+                log(&format!(
+                    "[{}] 🏁 MARKET END | asset={} | side={:?} | entry={:.4} | outcome={} | pnl={:.4} | {}",
+                    tag, asset, cycle.side, cycle.entry_price, outcome_str, final_pnl,
+                    if is_win { "WIN" } else { "LOSS" }
+                ));
+            } else {
+                log(&format!(
+                    "[{}] ⚠️  MARKET END | asset={} | open cycle exists but no last price point stored; cannot settle PnL",
+                    tag, asset
+                ));
+            }
+        }
+
+        // Hedged positions always settle: matched pairs pay 1.0 whichever side wins
+        if state.hedge.is_open() {
+            let hedge = std::mem::replace(&mut state.hedge, PositionState::NoPosition);
+            let final_pnl = hedge.settle(winner.unwrap_or(PositionSide::Flat));
+            state.total_pnl += final_pnl;
+            let is_win = final_pnl > Decimal::ZERO;
+            if is_win {
+                state.wins += 1;
+            } else {
+                state.losses += 1;
+            }
+            let shares = hedge.shares(PositionSide::LongUp).max(hedge.shares(PositionSide::LongDown));
+            let action = if is_win { "SETTLE_HEDGE_WIN" } else { "SETTLE_HEDGE_LOSS" };
+            self.ledger.closed(self.now_secs, asset, action, Decimal::ONE, shares, final_pnl, is_win);
+            log(&format!(
+                "[{}] 🏁 MARKET END | asset={} | side=Hedged | cost={:.4} | locked_profit={:.4} | outcome={} | pnl={:.4} | {}",
+                tag, asset, hedge.cost(), hedge.locked_profit(), outcome_str, final_pnl,
+                if is_win { "WIN" } else { "LOSS" }
+            ));
+        }
+
+        self.executor.market_ended(asset).await;
+
+        // ALWAYS log final summary for this market (even if no trades occurred)
+        log(&format!(
+            "[{}] 📊 MARKET SUMMARY | asset={} | total_pnl={:.4} | wins={} | losses={} | fund_used={:.4}",
+            tag, asset, state.total_pnl, state.wins, state.losses, state.total_fund_used
+        ));
+
+        // Indicators, price history and stats start from scratch for the new market
+        *state = AssetState::new(self.strategy.as_ref());
+    }
+
+    /// Process a snapshot and make trading decisions
+    async fn process_snapshot(&mut self, snapshot: &MarketSnapshot) -> Result<()> {
+        let tag = self.executor.tag();
+        self.now_secs = snapshot.captured_at_ms / 1000;
+        self.time_remaining_secs = snapshot.time_remaining_seconds;
+
+        // Check if market period changed (market ended)
+        let current_period = snapshot.period_timestamp;
+        if self.previous_period_timestamp.is_some_and(|prev| prev != current_period) {
+            let separator = format!("[{}] ════════════════════════════════════════════════════════════════════════════", tag);
+            log(&separator);
+            let assets = self.trading_assets.clone();
+            for asset in &assets {
+                self.handle_market_end(asset).await;
+            }
+            self.previous_period_timestamp = Some(current_period);
+            self.persist();
+            log(&separator);
+            log(&format!(
+                "[{}] 🔄 NEW MARKET | Resetting indicators, price history and market stats (pnl/wins/losses/fund)",
+                tag
+            ));
+        }
+        self.previous_period_timestamp = Some(current_period);
+
+        let assets = self.trading_assets.clone();
+        for asset in &assets {
+            let Some(price_point) = Self::snapshot_to_price_point(snapshot, asset) else {
+                continue;
+            };
+            if let Some(state) = self.assets.get_mut(asset) {
+                // Store the last price point and quotes (for settlement and order sizing)
+                state.last_price_point = Some(price_point.clone());
+                state.last_market_data = snapshot.market_for(asset).cloned();
+            }
+            let result = self.process_price_point(asset, &price_point).await;
+            // Journal every transition of this asset before anything else can go wrong
+            self.persist();
+            result?;
+        }
+
+        if self.reconcile_interval_secs > 0 && self.now_secs >= self.last_reconcile_secs + self.reconcile_interval_secs {
+            self.last_reconcile_secs = self.now_secs;
+            self.reconcile().await;
+        }
+        Ok(())
+    }
+
+    /// Book a filled entry as the asset's open cycle
+    fn start_cycle(&mut self, asset: &str, pending: &PendingEntry, filled: Decimal, fill_price: Option<Decimal>) -> Option<ActiveCycle> {
+        let tag = self.executor.tag();
+        let state = self.assets.get_mut(asset)?;
+        let (tp_offset, sl_offset) = self.strategy.exit_offsets(pending.side, pending.limit_price);
+        let cycle = ActiveCycle::open(pending.side, fill_price.unwrap_or(pending.limit_price), filled, tp_offset, sl_offset);
+        state.total_fund_used += cycle.entry_price * cycle.size;
+        state.current_cycle = Some(cycle.clone());
+        let (action, held) = match cycle.side {
+            PositionSide::LongUp => ("BUY_UP", &state.indicators.up),
+            _ => ("BUY_DOWN", &state.indicators.down),
+        };
+        let index = self.strategy.calculate_index(held);
+        self.ledger.opened(self.now_secs, asset, action, cycle.entry_price, cycle.size, index);
+
+        let msg = format!(
+            "[{}] ✅ CYCLE OPEN | asset={} | side={:?} | shares={} | entry={:.4} | TP={:.4} | SL={:.4}",
+            tag, asset, cycle.side, cycle.size, cycle.entry_price, cycle.tp_price, cycle.sl_price
+        );
+        log(&msg);
+        info!("{}", msg);
+        Some(cycle)
+    }
+
+    /// Open a cycle for a filled entry and place its TP order
+    async fn open_cycle(&mut self, asset: &str, pending: PendingEntry, filled: Decimal, fill_price: Option<Decimal>) {
+        let tag = self.executor.tag();
+        let Some(cycle) = self.start_cycle(asset, &pending, filled, fill_price) else {
+            return;
+        };
+
+        let delay = self.executor.fill_delay();
+        if !delay.is_zero() {
+            self.source.pause(delay).await;
+        }
+
+        // TP: resting LIMIT SELL of the held token at the TP price
+        if cycle.tp_price > Decimal::ONE {
+            log(&format!(
+                "[{}] ⏸️  NO LIMIT | asset={} | TP={:.4} out of [0,1] | waiting for SL or market end",
+                tag, asset, cycle.tp_price
+            ));
+            return;
+        }
+        self.rest_take_profit(asset, &cycle).await;
+    }
+
+    /// Place the resting TP SELL for the cycle's remaining shares
+    async fn rest_take_profit(&mut self, asset: &str, cycle: &ActiveCycle) {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get(asset) else {
+            return;
+        };
+        match state.order_for(asset, cycle.side, cycle.tp_price.round_dp(2), cycle.size) {
+            Some(order) => {
+                if let Err(e) = self.executor.place_take_profit(&order).await {
+                    log(&format!("[{}] ❌ Failed to place TP order: {} (will retry)", tag, e));
+                }
+            }
+            None => log(&format!("[{}] ❌ No token ID for {} TP order", tag, asset)),
+        }
+    }
+
+    /// Resolve a pending entry for `asset`. Returns true while the entry is still unresolved
+    /// (the rest of the tick is skipped, matching live fill confirmation).
+    async fn resolve_pending_entry(&mut self, asset: &str) -> bool {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return false;
+        };
+        let Some(pending) = state.pending_entry.clone() else {
+            return false;
+        };
+
+        // Poll before judging the timeout: an entry that filled in time still opens its cycle
+        match self.executor.poll_entry(asset).await {
+            Ok(FillStatus::Filled { size, price }) => {
+                state.pending_entry = None;
+                self.open_cycle(asset, pending, size, price).await;
+                return false;
+            }
+            Ok(FillStatus::Cancelled) => {
+                log(&format!("[{}] 🚫 ENTRY CANCELLED | asset={}", tag, asset));
+                state.pending_entry = None;
+                return false;
+            }
+            Ok(FillStatus::Pending) => {}
+            Err(e) => {
+                log(&format!("[{}] ⚠️  ENTRY PENDING | asset={} | fill check failed: {} (will retry)", tag, asset, e));
+            }
+        }
+
+        if self.now_secs.saturating_sub(pending.placed_at_secs) < ENTRY_TIMEOUT_SECS {
+            return true;
+        }
+        log(&format!("[{}] ⏳ ENTRY TIMEOUT | asset={} | cancelling entry", tag, asset));
+        state.pending_entry = None;
+        // Shares that filled up to the cancel still get a cycle and a TP
+        if let FillStatus::Filled { size, price } = self.executor.cancel_entry(asset).await {
+            self.open_cycle(asset, pending, size, price).await;
+        }
+        false
+    }
+
+    /// Process a single price point for `asset`
+    async fn process_price_point(&mut self, asset: &str, price_point: &PricePoint) -> Result<()> {
+        let tag = self.executor.tag();
+        let cfg = self.strategy.config().clone();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return Ok(());
+        };
+
+        // Update indicators for both tokens
+        state.indicators.add_prices(price_point.up_price, price_point.down_price);
+
+        // Compute trending indices for Up and Down tokens; the strategy decides on its own indicator state
+        let up_index = self.strategy.calculate_index(&state.indicators.up);
+        let down_index = self.strategy.calculate_index(&state.indicators.down);
+        let action = self.strategy.decide(price_point, &state.indicators);
+
+        let idx_name = cfg.index_type.label();
+        let fmt_index = |v: Option<f64>| match v {
+            Some(v) => format!("{:.*}", cfg.index_type.log_decimals(), v),
+            None => "n/a".to_string(),
+        };
+
+        // 0) A pending entry must fill (or time out) before anything else happens for this asset
+        if self.resolve_pending_entry(asset).await {
+            return Ok(());
+        }
+
+        // Hedged-entry legs and unwinds resolve over ticks without holding up the rest
+        self.resolve_pending_hedge(asset).await;
+        self.resolve_pending_unwind(asset).await;
+
+        // 1) Open cycle: check TP first, then SL
+        self.check_exits(asset, price_point).await;
+
+        // Market making: book quote fills and requote
+        self.update_quotes(asset).await;
+
+        // Matched Up+Down pairs (SL hedges, hedged entries, filled quotes) go back to USDC if enabled
+        self.merge_hedged_pairs(asset).await;
+
+        // 2) Flat: place an entry if the strategy says BUY and the entry filters pass.
+        // A held (or still filling) hedge only blocks another hedged entry.
+        let (flat, hedge_open) = self
+            .assets
+            .get(asset)
+            .map(|s| {
+                let hedge_busy = s.hedge.is_open() || s.pending_hedge.is_some() || s.pending_unwind.is_some();
+                (s.current_cycle.is_none() && s.pending_entry.is_none(), hedge_busy)
+            })
+            .unwrap_or((false, false));
+        if flat {
+            if let TradeAction::BuyBoth { up_price, down_price, shares, fee_rate } = action {
+                if !hedge_open {
+                    self.place_hedge(asset, up_price, down_price, shares, fee_rate).await;
+                    self.merge_hedged_pairs(asset).await;
+                }
+            }
+            let entry = match &action {
+                TradeAction::BuyUp { price, shares } => Some((PositionSide::LongUp, *price, *shares)),
+                TradeAction::BuyDown { price, shares } => Some((PositionSide::LongDown, *price, *shares)),
+                TradeAction::Hold { reason } => {
+                    log(&format!("[{}] ⏸️  ENTRY HELD | asset={} | {}", tag, asset, reason));
+                    None
+                }
+                _ => None,
+            };
+            if let Some((side, entry_price, shares)) = entry.filter(|(_, price, _)| *price > Decimal::ZERO) {
+                let indices = format!(
+                    "{}_up={} | {}_down={}",
+                    idx_name, fmt_index(up_index), idx_name, fmt_index(down_index)
+                );
+                self.place_entry(asset, price_point.timestamp, side, entry_price, shares, &indices).await;
+            }
+        }
+
+        // 3) Log index snapshot on every tick (whether or not we are in a cycle)
+        let stats = self.assets.get(asset).map(|s| s.stats_line()).unwrap_or_default();
+        let msg = match (up_index, down_index) {
+            (Some(_), Some(_)) => format!(
+                "[{}] 📈 INDEX    | asset={} | {}_up={} | {}_down={} | {}",
+                tag, asset, idx_name, fmt_index(up_index), idx_name, fmt_index(down_index), stats
+            ),
+            _ => format!("[{}] 📈 INDEX    | asset={} | {}=n/a | {}", tag, asset, idx_name, stats),
+        };
+        log(&msg);
+
+        Ok(())
+    }
+
+    /// TP/SL checks for the asset's open cycle (TP first, then SL unless the strategy holds through it)
+    async fn check_exits(&mut self, asset: &str, price_point: &PricePoint) {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let Some(mut cycle) = state.current_cycle.clone() else {
+            return;
+        };
+        let (held_price, opposite_price) = cycle.held_and_opposite(price_point);
+
+        // A stop loss still filling is resolved first; the TP is checked once it has
+        let sl_fill = match self.resolve_pending_stop_loss(asset).await {
+            FillStatus::Pending => return,
+            fill => fill,
+        };
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+
+        // A resting TP order reports its own fills; without one the TP is judged from the price
+        let tp = match self.executor.poll_take_profit(asset).await {
+            Ok(Some(fill)) => TpStatus::Fills(fill),
+            Ok(None) => TpStatus::NotResting,
+            Err(e) => {
+                log(&format!("[{}] ⚠️  TP CHECK FAILED | asset={} | {} (will retry)", tag, asset, e));
+                TpStatus::Unknown
+            }
+        };
+        match tp {
+            TpStatus::Fills(fill) if fill.shares > Decimal::ZERO => {
+                let sold = fill.shares.min(cycle.size);
+                let pnl = (fill.price - cycle.entry_price) * sold;
+                state.total_pnl += pnl;
+                cycle.size -= sold;
+                self.ledger.closed(self.now_secs, asset, "TP", fill.price, sold, pnl, pnl > Decimal::ZERO);
+                if cycle.size < DUST_SHARES {
+                    state.wins += 1;
+                    let msg = format!(
+                        "[{}] ✅ TP HIT   | asset={} | side={:?} | entry={:.4} | tp={:.4} | size={:.4} | fill_price={:.4} | pnl={:.4}",
+                        tag, asset, cycle.side, cycle.entry_price, cycle.tp_price, sold, fill.price, pnl
+                    );
+                    log(&msg);
+                    info!("{}", msg);
+                    state.current_cycle = None;
+                    self.executor.cycle_closed(asset, ExitReason::TakeProfit).await;
+                    if let FillStatus::Filled { size, price } = sl_fill {
+                        self.hold_unmatched_stop_loss(asset, cycle.side.opposite(), size, price);
+                    }
+                    return;
+                }
+                log(&format!(
+                    "[{}] ✅ TP PARTIAL | asset={} | side={:?} | sold={} | fill_price={:.4} | remaining={} | pnl={:.4}",
+                    tag, asset, cycle.side, sold, fill.price, cycle.size, pnl
+                ));
+                state.current_cycle = Some(cycle.clone());
+            }
+            // A venue TP only exits through its order: one that never went out is placed again
+            TpStatus::NotResting if self.executor.rests_take_profit() => {
+                if cycle.tp_price <= Decimal::ONE {
+                    log(&format!("[{}] ⚠️  TP MISSING | asset={} | no TP order resting | placing again", tag, asset));
+                    self.rest_take_profit(asset, &cycle).await;
+                }
+            }
+            TpStatus::NotResting if held_price > 0.0 && Decimal::from_f64(held_price).is_some_and(|p| cycle.tp_hit(p)) => {
+                let pnl = cycle.tp_pnl();
+                state.total_pnl += pnl;
+                state.wins += 1;
+                self.ledger.closed(self.now_secs, asset, "TP", cycle.tp_price, cycle.size, pnl, true);
+                let msg = format!(
+                    "[{}] ✅ TP HIT   | asset={} | side={:?} | entry={:.4} | tp={:.4} | size={:.4} | pnl={:.4}",
+                    tag, asset, cycle.side, cycle.entry_price, cycle.tp_price, cycle.size, pnl
+                );
+                log(&msg);
+                info!("{}", msg);
+                state.current_cycle = None;
+                self.executor.cycle_closed(asset, ExitReason::TakeProfit).await;
+                return;
+            }
+            TpStatus::Fills(_) | TpStatus::NotResting | TpStatus::Unknown => {}
+        }
+
+        // A stop loss that filled since the last tick hedges what the TP left
+        if let FillStatus::Filled { size, price } = sl_fill {
+            self.finish_stop_loss(asset, size, price).await;
+            return;
+        }
+
+        // SL: the opposite token's ask reached (1 - SL)
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let Some(opposite_ask) = Decimal::from_f64(opposite_price).filter(|_| opposite_price > 0.0) else {
+            return;
+        };
+        if !cycle.sl_hit(opposite_ask) {
+            return;
+        }
+
+        if let Some(reason) = self.strategy.hold_through_stop_loss(cycle.side, &state.indicators) {
+            log(&format!("[{}] ⏸️  SL SKIPPED | asset={} | side={:?} | {}", tag, asset, cycle.side, reason));
+            return;
+        }
+
+        // Stop loss = BUY the opposite token at (1 - SL), which hedges the held token
+        let Some(order) = state.order_for(asset, cycle.side.opposite(), (Decimal::ONE - cycle.sl_price).round_dp(2), cycle.size) else {
+            log(&format!("[{}] ❌ No opposite token ID for {} SL order", tag, asset));
+            return;
+        };
+        let pending = PendingStopLoss {
+            price: order.price,
+            opposite_ask,
+            placed_at_secs: self.now_secs,
+        };
+        match self.executor.place_taker(TakerKind::StopLoss, &order).await {
+            Ok(FillStatus::Filled { size, price }) => {
+                state.pending_stop_loss = Some(pending);
+                self.finish_stop_loss(asset, size, price).await;
+            }
+            Ok(FillStatus::Pending) => {
+                log(&format!(
+                    "[{}] ⏳ SL PENDING | asset={} | side={:?} | opposite_ask={:.4} | target=(1-SL)={:.4} | waiting for fills",
+                    tag, asset, cycle.side, opposite_ask, order.price
+                ));
+                state.pending_stop_loss = Some(pending);
+            }
+            Ok(FillStatus::Cancelled) => log(&format!(
+                "[{}] ⚠️  SL NOT FILLED | asset={} | side={:?} | opposite_ask={:.4} | target=(1-SL)={:.4} | keeping cycle open (will retry)",
+                tag, asset, cycle.side, opposite_ask, order.price
+            )),
+            Err(e) => log(&format!("[{}] ❌ Failed to place SL order: {} (will retry)", tag, e)),
+        }
+    }
+
+    /// Resolve a pending stop loss for `asset`: `Filled` once it has filled (or its timeout cancelled
+    /// a partial fill), `Pending` while it may still fill, `Cancelled` when none is pending or it missed.
+    /// A filled stop loss stays in `pending_stop_loss` for `finish_stop_loss`.
+    async fn resolve_pending_stop_loss(&mut self, asset: &str) -> FillStatus {
+        let tag = self.executor.tag();
+        let Some(pending) = self.assets.get(asset).and_then(|s| s.pending_stop_loss.clone()) else {
+            return FillStatus::Cancelled;
+        };
+        let status = match self.executor.poll_taker(asset, TakerKind::StopLoss).await {
+            Ok(FillStatus::Pending) if self.now_secs.saturating_sub(pending.placed_at_secs) < TAKER_TIMEOUT_SECS => {
+                return FillStatus::Pending;
+            }
+            Ok(FillStatus::Pending) => {
+                log(&format!("[{}] ⏳ SL TIMEOUT | asset={} | cancelling the unfilled rest", tag, asset));
+                self.executor.cancel_taker(asset, TakerKind::StopLoss).await
+            }
+            Ok(status) => status,
+            Err(e) => {
+                log(&format!("[{}] ⚠️  SL PENDING | asset={} | fill check failed: {} (will retry)", tag, asset, e));
+                return FillStatus::Pending;
+            }
+        };
+        if status == FillStatus::Cancelled {
+            if let Some(state) = self.assets.get_mut(asset) {
+                state.pending_stop_loss = None;
+            }
+            log(&format!(
+                "[{}] ⚠️  SL NOT FILLED | asset={} | opposite_ask={:.4} | target=(1-SL)={:.4} | keeping cycle open (will retry)",
+                tag, asset, pending.opposite_ask, pending.price
+            ));
+        }
+        status
+    }
+
+    /// Book the fill of the pending stop loss: the cycle's held shares and the bought opposite
+    /// shares are held together to resolution (or merged); PnL is booked when the hedge settles
+    async fn finish_stop_loss(&mut self, asset: &str, filled: Decimal, fill_price: Option<Decimal>) {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let (Some(pending), Some(cycle)) = (state.pending_stop_loss.take(), state.current_cycle.take()) else {
+            return;
+        };
+        let filled = filled.min(cycle.size);
+        let price = fill_price.unwrap_or(pending.price);
+        state.hedge.add_fill(cycle.side, cycle.entry_price, cycle.size);
+        state.hedge.add_fill(cycle.side.opposite(), price, filled);
+        state.total_fund_used += price * filled;
+        self.ledger.opened(self.now_secs, asset, "SL_HEDGE", price, filled, None);
+        let msg = format!(
+            "[{}] ❌ SL HIT   | asset={} | side={:?} | entry={:.4} | sl={:.4} | opposite_ask={:.4} | target=(1-SL)={:.4} | size={:.4} | hedged={:.4} | fill_price={:.4} | locked_profit={:.4}",
+            tag, asset, cycle.side, cycle.entry_price, cycle.sl_price, pending.opposite_ask, pending.price, cycle.size, filled, price, state.hedge.locked_profit()
+        );
+        log(&msg);
+        info!("{}", msg);
+        if filled < cycle.size {
+            log(&format!(
+                "[{}] ⚠️  HEDGE UNBALANCED | asset={} | held={} | hedged={} | unmatched shares ride on the outcome",
+                tag, asset, cycle.size, filled
+            ));
+        }
+        self.executor.cycle_closed(asset, ExitReason::StopLoss).await;
+    }
+
+    /// The TP sold the whole cycle while its stop loss was filling: the opposite shares the
+    /// stop loss bought have nothing left to hedge and ride on the outcome
+    fn hold_unmatched_stop_loss(&mut self, asset: &str, side: PositionSide, filled: Decimal, fill_price: Option<Decimal>) {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let Some(pending) = state.pending_stop_loss.take() else {
+            return;
+        };
+        let price = fill_price.unwrap_or(pending.price);
+        state.hedge.add_fill(side, price, filled);
+        state.total_fund_used += price * filled;
+        self.ledger.opened(self.now_secs, asset, "SL_HEDGE", price, filled, None);
+        log(&format!(
+            "[{}] ⚠️  SL UNMATCHED | asset={} | token={} | shares={} | fill_price={:.4} | TP closed the cycle first; shares ride on the outcome",
+            tag, asset, token_label(side), filled, price
+        ));
+    }
+
+    /// With `merge_hedged_pairs` enabled, merge the hedge's matched Up+Down pairs back into USDC
+    /// and book their PnL. After a failed merge the pairs are held to resolution for the rest of the market.
+    async fn merge_hedged_pairs(&mut self, asset: &str) {
+        let tag = self.executor.tag();
+        if !self.strategy.config().merge_hedged_pairs {
+            return;
+        }
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let pairs = state.hedge.matched_pairs();
+        if pairs <= Decimal::ZERO || state.merge_failed {
+            return;
+        }
+        let Some(condition_id) = state.last_market_data.as_ref().map(|m| m.condition_id.clone()) else {
+            return;
+        };
+
+        if let Err(e) = self.executor.merge_pairs(asset, &condition_id, pairs).await {
+            log(&format!(
+                "[{}] ❌ MERGE FAILED | asset={} | pairs={} | {} | holding pairs to resolution",
+                tag, asset, pairs, e
+            ));
+            state.merge_failed = true;
+            return;
+        }
+        let pnl = state.hedge.merge(pairs);
+        state.total_pnl += pnl;
+        self.ledger.closed(self.now_secs, asset, "MERGE", Decimal::ONE, pairs, pnl, pnl > Decimal::ZERO);
+        if pnl > Decimal::ZERO {
+            state.wins += 1;
+        } else {
+            state.losses += 1;
+        }
+        let (up_left, down_left) = match state.hedge {
+            PositionState::Hedged { up_size, down_size, .. } => (up_size, down_size),
+            _ => (Decimal::ZERO, Decimal::ZERO),
+        };
+        let msg = format!(
+            "[{}] 🔁 MERGED   | asset={} | pairs={} | pnl={:.4} | remaining_up={} | remaining_down={} | {}",
+            tag, asset, pairs, pnl, up_left, down_left,
+            if pnl > Decimal::ZERO { "WIN" } else { "LOSS" }
+        );
+        log(&msg);
+        info!("{}", msg);
+    }
+
+    /// Apply the timing and liquidity entry filters, then hand the entry to the executor
+    async fn place_entry(&mut self, asset: &str, period_start: u64, side: PositionSide, entry_price: Decimal, shares: Decimal, indices: &str) {
+        let tag = self.executor.tag();
+        let elapsed = self.now_secs.saturating_sub(period_start);
+        if !self.trading_started(asset) {
+            return;
+        }
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+
+        // Near-certain prices early in the market have little upside and full downside
+        if entry_price > LATE_ENTRY_PRICE && elapsed < LATE_ENTRY_ELAPSED_SECS {
+            log(&format!(
+                "[{}] ⏸️  ENTRY SKIPPED | asset={} | entry={:.4} > {} | elapsed={}s < 13m | not placing entry order",
+                tag, asset, entry_price, LATE_ENTRY_PRICE, elapsed
+            ));
+            return;
+        }
+
+        // Cap size at the liquidity the book can absorb at the entry limit
+        let size = state.depth_limited_shares(side, entry_price, shares);
+        if size < shares {
+            log(&format!(
+                "[{}] 📉 DEPTH LIMITED | asset={} | token={} | requested={} | fillable={} | entry={:.4}",
+                tag, asset, token_label(side), shares, size, entry_price
+            ));
+            if size.is_zero() {
+                return; // Nothing on the book at the entry price
+            }
+        }
+
+        let Some(order) = state.order_for(asset, side, entry_price.round_dp(2), size) else {
+            log(&format!("[{}] ❌ No {} token ID for {} entry", tag, token_label(side), asset));
+            return;
+        };
+
+        let (emoji, label) = if side == PositionSide::LongUp { ("🟢", "BUY UP  ") } else { ("🔴", "BUY DOWN") };
+        let msg = format!(
+            "[{}] {} {} | asset={} | shares={} | entry_limit={:.4} | {}",
+            tag, emoji, label, asset, size, entry_price, indices
+        );
+        log(&msg);
+        info!("{}", msg);
+
+        let pending = PendingEntry {
+            side,
+            limit_price: entry_price,
+            placed_at_secs: self.now_secs,
+        };
+        match self.executor.place_entry(&order).await {
+            Ok(FillStatus::Filled { size, price }) => self.open_cycle(asset, pending, size, price).await,
+            Ok(FillStatus::Pending) => state.pending_entry = Some(pending),
+            Ok(FillStatus::Cancelled) => log(&format!("[{}] 🚫 ENTRY CANCELLED | asset={}", tag, asset)),
+            Err(e) => log(&format!("[{}] ❌ Failed to place entry order: {}", tag, e)),
+        }
+    }
+
+    /// Market making: add fills of the resting quotes to the inventory, then move each quote
+    /// to where the strategy wants it (cancel + place). No-op for taker strategies.
+    async fn update_quotes(&mut self, asset: &str) {
+        let tag = self.executor.tag();
+        // Checked quietly: a quoting strategy asks every tick
+        let trading_window = self.in_trading_window();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+
+        for side in [PositionSide::LongUp, PositionSide::LongDown] {
+            let resting = if side == PositionSide::LongUp { &mut state.quotes.up } else { &mut state.quotes.down };
+            let Some(quote) = resting.as_mut() else {
+                continue;
+            };
+            let book = match side {
+                PositionSide::LongUp => state.last_market_data.as_ref().and_then(|m| m.up_token.as_ref()),
+                _ => state.last_market_data.as_ref().and_then(|m| m.down_token.as_ref()),
+            };
+            let filled = match self.executor.poll_quote(asset, token_label(side), book).await {
+                Ok(filled) => filled.min(quote.size),
+                Err(e) => {
+                    log(&format!("[{}] ⚠️  QUOTE CHECK FAILED | asset={} | token={} | {} (will retry)", tag, asset, token_label(side), e));
+                    continue;
+                }
+            };
+            if filled <= Decimal::ZERO {
+                continue;
+            }
+            let price = quote.price;
+            quote.size -= filled;
+            if quote.size <= Decimal::ZERO {
+                *resting = None;
+            }
+            state.hedge.add_fill(side, price, filled);
+            state.total_fund_used += price * filled;
+            let action = if side == PositionSide::LongUp { "QUOTE_UP" } else { "QUOTE_DOWN" };
+            self.ledger.opened(self.now_secs, asset, action, price, filled, None);
+            let msg = format!(
+                "[{}] 💱 QUOTE FILLED | asset={} | token={} | price={:.2} | shares={} | net_up={} | locked_profit={:.4}",
+                tag, asset, token_label(side), price, filled, state.hedge.net_up_shares(), state.hedge.locked_profit()
+            );
+            log(&msg);
+            info!("{}", msg);
+        }
+
+        let Some(market) = state.last_market_data.as_ref() else {
+            return;
+        };
+        let Some(mut wanted) = self.strategy.quotes(market, &state.hedge, self.time_remaining_secs) else {
+            return;
+        };
+        // Trading start gate: no quotes until the market has at most N minutes left
+        if !trading_window {
+            wanted = Quotes::default();
+        }
+
+        for (side, want) in [(PositionSide::LongUp, wanted.up), (PositionSide::LongDown, wanted.down)] {
+            let resting = if side == PositionSide::LongUp { state.quotes.up } else { state.quotes.down };
+            match (resting, want) {
+                // A partly filled quote keeps resting at the same price
+                (Some(r), Some(w)) if r.price == w.price => continue,
+                (None, None) => continue,
+                _ => {}
+            }
+
+            if resting.is_some() {
+                self.executor.cancel_quote(asset, token_label(side)).await;
+                if want.is_none() {
+                    log(&format!(
+                        "[{}] 🧹 QUOTE PULLED | asset={} | token={} | remaining={}s",
+                        tag, asset, token_label(side), self.time_remaining_secs
+                    ));
+                }
+            }
+            let mut placed: Option<Quote> = None;
+            if let Some(w) = want {
+                match state.order_for(asset, side, w.price, w.size) {
+                    Some(order) => match self.executor.place_quote(&order).await {
+                        Ok(()) => {
+                            log(&format!(
+                                "[{}] 📝 QUOTE    | asset={} | token={} | bid={:.2} | shares={} | net_up={} | remaining={}s",
+                                tag, asset, token_label(side), w.price, w.size, state.hedge.net_up_shares(), self.time_remaining_secs
+                            ));
+                            placed = Some(w);
+                        }
+                        Err(e) => log(&format!("[{}] ❌ Failed to place quote: {}", tag, e)),
+                    },
+                    None => log(&format!("[{}] ❌ No {} token ID for {} quote", tag, token_label(side), asset)),
+                }
+            }
+            if side == PositionSide::LongUp {
+                state.quotes.up = placed;
+            } else {
+                state.quotes.down = placed;
+            }
+        }
+    }
+
+    /// Whether the market has at most `trading_start_when_remaining_minutes` left (per the snapshot's time remaining)
+    fn in_trading_window(&self) -> bool {
+        self.strategy
+            .config()
+            .trading_start_when_remaining_minutes
+            .is_none_or(|required| self.time_remaining_secs <= required * 60)
+    }
+
+    /// Trading start gate: `in_trading_window`, logging why an order waits
+    fn trading_started(&self, asset: &str) -> bool {
+        if self.in_trading_window() {
+            return true;
+        }
+        let required = self.strategy.config().trading_start_when_remaining_minutes.unwrap_or_default();
+        log(&format!(
+            "[{}] ⏸️  TRADING NOT STARTED | asset={} | remaining={}m{:02}s > {}m | waiting for market to reach {}m remaining",
+            self.executor.tag(), asset, self.time_remaining_secs / 60, self.time_remaining_secs % 60, required, required
+        ));
+        false
+    }
+
+    /// Buy Up and Down in matched size (capped at the thinner book) and hold both to resolution
+    async fn place_hedge(&mut self, asset: &str, up_price: Decimal, down_price: Decimal, shares: Decimal, fee_rate: Decimal) {
+        let tag = self.executor.tag();
+        if up_price <= Decimal::ZERO || down_price <= Decimal::ZERO {
+            return;
+        }
+        if !self.trading_started(asset) {
+            return;
+        }
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+
+        // Both legs must fill in the same size, so the thinner book sets it
+        let size = state
+            .depth_limited_shares(PositionSide::LongUp, up_price, shares)
+            .min(state.depth_limited_shares(PositionSide::LongDown, down_price, shares));
+        if size < shares {
+            log(&format!(
+                "[{}] 📉 DEPTH LIMITED | asset={} | token=UP+DOWN | requested={} | fillable={} | up={:.4} | down={:.4}",
+                tag, asset, shares, size, up_price, down_price
+            ));
+            if size.is_zero() {
+                return;
+            }
+        }
+
+        let (Some(up_order), Some(down_order)) = (
+            state.order_for(asset, PositionSide::LongUp, up_price.round_dp(2), size),
+            state.order_for(asset, PositionSide::LongDown, down_price.round_dp(2), size),
+        ) else {
+            log(&format!("[{}] ❌ No UP/DOWN token IDs for {} hedged entry", tag, asset));
+            return;
+        };
+
+        let expected = PositionState::hedged(up_price, size, down_price, size, fee_rate);
+        let msg = format!(
+            "[{}] 🔒 BUY BOTH | asset={} | shares={} | up_limit={:.4} | down_limit={:.4} | pair_cost={:.4} | expected_profit={:.4}",
+            tag, asset, size, up_price, down_price, expected.cost() / size, expected.locked_profit()
+        );
+        log(&msg);
+        info!("{}", msg);
+
+        let mut pending = PendingHedge {
+            up: HedgeLeg { limit_price: up_order.price, fill: None },
+            down: HedgeLeg { limit_price: down_order.price, fill: None },
+            fee_rate,
+            placed_at_secs: self.now_secs,
+        };
+        for (side, order, leg) in [(PositionSide::LongUp, &up_order, &mut pending.up), (PositionSide::LongDown, &down_order, &mut pending.down)] {
+            match self.executor.place_taker(TakerKind::HedgeLeg(side), order).await {
+                Ok(FillStatus::Pending) => {}
+                Ok(status) => leg.resolved(status),
+                // The other leg may have gone out: it is still resolved (and unwound if it fills)
+                Err(e) => {
+                    log(&format!("[{}] ❌ Failed to place hedged entry {} leg: {}", tag, token_label(side), e));
+                    leg.resolved(FillStatus::Cancelled);
+                }
+            }
+        }
+        if pending.up.fill.is_some() && pending.down.fill.is_some() {
+            self.open_hedge(asset, pending, true).await;
+        } else if let Some(state) = self.assets.get_mut(asset) {
+            state.pending_hedge = Some(pending);
+        }
+    }
+
+    /// Poll the legs of a pending hedged entry; legs still unfilled after `TAKER_TIMEOUT_SECS` are
+    /// cancelled. Once both legs have resolved, the hedge opens.
+    async fn resolve_pending_hedge(&mut self, asset: &str) {
+        let tag = self.executor.tag();
+        let Some(mut pending) = self.assets.get(asset).and_then(|s| s.pending_hedge.clone()) else {
+            return;
+        };
+        let timed_out = self.now_secs.saturating_sub(pending.placed_at_secs) >= TAKER_TIMEOUT_SECS;
+        for (side, leg) in [(PositionSide::LongUp, &mut pending.up), (PositionSide::LongDown, &mut pending.down)] {
+            if leg.fill.is_some() {
+                continue;
+            }
+            let kind = TakerKind::HedgeLeg(side);
+            match self.executor.poll_taker(asset, kind).await {
+                Ok(FillStatus::Pending) if !timed_out => {}
+                Ok(FillStatus::Pending) => leg.resolved(self.executor.cancel_taker(asset, kind).await),
+                Ok(status) => leg.resolved(status),
+                Err(e) => {
+                    log(&format!("[{}] ⚠️  HEDGE PENDING | asset={} | token={} | fill check failed: {}", tag, asset, token_label(side), e));
+                    if timed_out {
+                        leg.resolved(self.executor.cancel_taker(asset, kind).await);
+                    }
+                }
+            }
+        }
+        let resolved = pending.up.fill.is_some() && pending.down.fill.is_some();
+        if resolved {
+            if let Some(state) = self.assets.get_mut(asset) {
+                state.pending_hedge = None;
+            }
+            self.open_hedge(asset, pending, true).await;
+        } else if let Some(state) = self.assets.get_mut(asset) {
+            state.pending_hedge = Some(pending);
+        }
+    }
+
+    /// Hold the filled legs of a resolved hedged entry to resolution. With `unwind`, the shares
+    /// one leg bought beyond the other are sold at the best bid instead of riding on the outcome.
+    async fn open_hedge(&mut self, asset: &str, pending: PendingHedge, unwind: bool) {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let ((up_filled, up_price), (down_filled, down_price)) = (pending.up.fill.unwrap_or_default(), pending.down.fill.unwrap_or_default());
+        if up_filled.is_zero() && down_filled.is_zero() {
+            log(&format!("[{}] 🚫 ENTRY CANCELLED | asset={} | neither leg filled", tag, asset));
+            return;
+        }
+
+        let hedge = PositionState::hedged(up_price, up_filled, down_price, down_filled, pending.fee_rate);
+        state.total_fund_used += hedge.cost();
+        let shares = up_filled.max(down_filled);
+        self.ledger.opened(self.now_secs, asset, "BUY_BOTH", hedge.cost() / shares, shares, None);
+        let msg = format!(
+            "[{}] ✅ HEDGE OPEN | asset={} | up_shares={} | down_shares={} | cost={:.4} | locked_profit={:.4} | holding to resolution",
+            tag, asset, up_filled, down_filled, hedge.cost(), hedge.locked_profit()
+        );
+        log(&msg);
+        info!("{}", msg);
+        state.hedge = hedge;
+
+        let (side, excess) = if up_filled > down_filled {
+            (PositionSide::LongUp, up_filled - down_filled)
+        } else {
+            (PositionSide::LongDown, down_filled - up_filled)
+        };
+        if excess < DUST_SHARES {
+            return;
+        }
+        let bid = state.token(side).and_then(|t| t.bid).filter(|_| unwind);
+        let Some(order) = bid.and_then(|bid| state.order_for(asset, side, bid, excess)) else {
+            log(&format!(
+                "[{}] ⚠️  HEDGE UNBALANCED | asset={} | up_shares={} | down_shares={} | unmatched shares ride on the outcome",
+                tag, asset, up_filled, down_filled
+            ));
+            return;
+        };
+        log(&format!(
+            "[{}] ⚠️  HEDGE UNBALANCED | asset={} | up_shares={} | down_shares={} | selling {} unmatched {} shares at the bid {:.2}",
+            tag, asset, up_filled, down_filled, excess, token_label(side), order.price
+        ));
+        let pending = PendingUnwind { side, price: order.price, placed_at_secs: self.now_secs };
+        match self.executor.place_taker(TakerKind::Unwind(side), &order).await {
+            Ok(FillStatus::Filled { size, price }) => self.book_unwind(asset, &pending, size, price),
+            Ok(FillStatus::Pending) => state.pending_unwind = Some(pending),
+            Ok(FillStatus::Cancelled) => log(&format!("[{}] ⚠️  UNWIND NOT FILLED | asset={} | unmatched shares ride on the outcome", tag, asset)),
+            Err(e) => log(&format!("[{}] ❌ Failed to place unwind order: {} | unmatched shares ride on the outcome", tag, e)),
+        }
+    }
+
+    /// Poll a pending unwind; its unfilled rest is cancelled after `TAKER_TIMEOUT_SECS` and rides on the outcome
+    async fn resolve_pending_unwind(&mut self, asset: &str) {
+        let tag = self.executor.tag();
+        let Some(pending) = self.assets.get(asset).and_then(|s| s.pending_unwind.clone()) else {
+            return;
+        };
+        let kind = TakerKind::Unwind(pending.side);
+        let status = match self.executor.poll_taker(asset, kind).await {
+            Ok(FillStatus::Pending) if self.now_secs.saturating_sub(pending.placed_at_secs) < TAKER_TIMEOUT_SECS => return,
+            Ok(FillStatus::Pending) => self.executor.cancel_taker(asset, kind).await,
+            Ok(status) => status,
+            Err(e) => {
+                log(&format!("[{}] ⚠️  UNWIND PENDING | asset={} | fill check failed: {} (will retry)", tag, asset, e));
+                return;
+            }
+        };
+        if let Some(state) = self.assets.get_mut(asset) {
+            state.pending_unwind = None;
+        }
+        match status {
+            FillStatus::Filled { size, price } => self.book_unwind(asset, &pending, size, price),
+            _ => log(&format!("[{}] ⚠️  UNWIND NOT FILLED | asset={} | unmatched shares ride on the outcome", tag, asset)),
+        }
+    }
+
+    /// Take the sold shares of an unwind out of the hedge and book their PnL
+    fn book_unwind(&mut self, asset: &str, pending: &PendingUnwind, sold: Decimal, fill_price: Option<Decimal>) {
+        let tag = self.executor.tag();
+        let Some(state) = self.assets.get_mut(asset) else {
+            return;
+        };
+        let price = fill_price.unwrap_or(pending.price);
+        let sold = sold.min(state.hedge.shares(pending.side));
+        let pnl = price * sold - state.hedge.remove_shares(pending.side, sold);
+        state.total_pnl += pnl;
+        if pnl > Decimal::ZERO {
+            state.wins += 1;
+        } else {
+            state.losses += 1;
+        }
+        self.ledger.closed(self.now_secs, asset, "UNWIND", price, sold, pnl, pnl > Decimal::ZERO);
+        let msg = format!(
+            "[{}] ↩️  UNWIND   | asset={} | token={} | sold={} | fill_price={:.4} | pnl={:.4} | locked_profit={:.4}",
+            tag, asset, token_label(pending.side), sold, price, pnl, state.hedge.locked_profit()
+        );
+        log(&msg);
+        info!("{}", msg);
+    }
+
+    /// Settle every asset's current market (a finite source ended with positions still open)
+    pub async fn end_markets(&mut self) {
+        let assets = self.trading_assets.clone();
+        for asset in &assets {
+            self.handle_market_end(asset).await;
+        }
+    }
+
+    /// Run the trading loop until the data source is exhausted (never, for the live monitor)
+    pub async fn run(&mut self) -> Result<()> {
+        let tag = self.executor.tag();
+        if !crate::events_muted() {
+            println!("{}", self.executor.banner());
+            println!("   Strategy      : {}", self.strategy.name());
+            println!("   Markets       : {:?}", self.trading_assets);
+            println!("   Initial equity: ${:.2}", self.capital);
+            println!("   Check interval: {} ms", self.check_interval_ms);
+        }
+        info!("{}", self.executor.banner());
+        info!("Strategy: {}", self.strategy.name());
+        info!("Markets: {:?}", self.trading_assets);
+        info!("Initial capital: ${:.2}", self.capital);
+        info!("Check interval: {}ms", self.check_interval_ms);
+
+        let check_interval = Duration::from_millis(self.check_interval_ms);
+
+        loop {
+            match self.source.next_snapshot().await {
+                Ok(Some(snapshot)) => {
+                    if let Err(e) = self.process_snapshot(&snapshot).await {
+                        warn!("Error processing snapshot: {}", e);
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Error fetching market data: {}", e);
+                }
+            }
+
+            self.source.wait(check_interval).await;
+        }
+
+        // Only a finite source (replay) gets here
+        for (asset, state) in &self.assets {
+            log(&format!(
+                "[{}] ⏹️  DATA SOURCE EXHAUSTED | asset={} | total_pnl={:.4} | wins={} | losses={} | fund_used={:.4} | open_cycle={}",
+                tag, asset, state.total_pnl, state.wins, state.losses, state.total_fund_used,
+                state.current_cycle.is_some() || state.hedge.is_open()
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+// WebSocket market-data feed (CLOB market channel)
+//
+// Keeps a local order book per subscribed token from `book` snapshots and
+// `price_change` deltas, so the monitor can read best bid/ask without REST calls.
+
+use crate::models::{BookLevel, TokenPrice};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{watch, Notify};
+use tokio::time::{sleep, Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// Default CLOB market channel endpoint
+pub const DEFAULT_MARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+
+/// Interval between PING keep-alives (server drops idle sockets)
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+/// A connection that has sent nothing (not even a PONG) for this long is treated as dead
+const SILENCE_TIMEOUT: Duration = Duration::from_secs(2 * PING_INTERVAL.as_secs());
+/// Reconnect backoff bounds
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Local order book for one token (price → size)
+#[derive(Debug, Clone, Default)]
+struct LocalBook {
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl LocalBook {
+    fn best_bid(&self) -> Option<Decimal> {
+        self.bids.keys().next_back().copied()
+    }
+
+    fn best_ask(&self) -> Option<Decimal> {
+        self.asks.keys().next().copied()
+    }
+
+    /// Best `depth` bid levels, highest first
+    fn bid_levels(&self, depth: usize) -> Vec<BookLevel> {
+        self.bids.iter().rev().take(depth).map(|(&price, &size)| BookLevel { price, size }).collect()
+    }
+
+    /// Best `depth` ask levels, lowest first
+    fn ask_levels(&self, depth: usize) -> Vec<BookLevel> {
+        self.asks.iter().take(depth).map(|(&price, &size)| BookLevel { price, size }).collect()
+    }
+
+    /// Set (or remove, when size is zero) one level on the given side
+    fn set_level(&mut self, side: &str, price: Decimal, size: Decimal) {
+        let levels = if side.eq_ignore_ascii_case("BUY") { &mut self.bids } else { &mut self.asks };
+        if size.is_zero() {
+            levels.remove(&price);
+        } else {
+            levels.insert(price, size);
+        }
+    }
+}
+
+/// Streaming subscriber for the CLOB market channel.
+///
+/// The connection runs in a background task that reconnects with backoff and
+/// resubscribes whenever the token set changes (e.g. on 15-minute rollover).
+pub struct MarketFeed {
+    books: RwLock<HashMap<String, LocalBook>>,
+    connected: AtomicBool,
+    updates: Notify,
+    subscriptions: watch::Sender<Vec<String>>,
+}
+
+impl MarketFeed {
+    /// Start the feed task for `url`. No connection is opened until tokens are subscribed.
+    pub fn spawn(url: String) -> Arc<Self> {
+        let (subscriptions, subscriptions_rx) = watch::channel(Vec::new());
+        let feed = Arc::new(Self {
+            books: RwLock::new(HashMap::new()),
+            connected: AtomicBool::new(false),
+            updates: Notify::new(),
+            subscriptions,
+        });
+        tokio::spawn(Self::run(Arc::clone(&feed), url, subscriptions_rx));
+        feed
+    }
+
+    /// Replace the subscribed token set. A no-op if the set is unchanged.
+    pub fn subscribe(&self, mut token_ids: Vec<String>) {
+        token_ids.sort();
+        token_ids.dedup();
+        self.subscriptions.send_if_modified(|current| {
+            if *current == token_ids {
+                false
+            } else {
+                *current = token_ids;
+                true
+            }
+        });
+    }
+
+    /// Whether the socket is currently connected and subscribed
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Best bid/ask and the top `depth` levels for a token from the live book.
+    /// Returns None when the socket is down or no book has been received yet,
+    /// so callers can fall back to REST polling.
+    pub fn quote(&self, token_id: &str, depth: usize) -> Option<TokenPrice> {
+        if !self.is_connected() {
+            return None;
+        }
+        let books = self.books.read().ok()?;
+        let book = books.get(token_id)?;
+        Some(TokenPrice {
+            token_id: token_id.to_string(),
+            bid: book.best_bid(),
+            ask: book.best_ask(),
+            bids: book.bid_levels(depth),
+            asks: book.ask_levels(depth),
+        })
+    }
+
+    /// Wait until the next book update (or until `timeout` elapses)
+    pub async fn wait_for_update(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.updates.notified()).await;
+    }
+
+    /// Connection loop: connect, subscribe, stream; reconnect on error or token change.
+    async fn run(feed: Arc<Self>, url: String, mut subscriptions: watch::Receiver<Vec<String>>) {
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            let tokens = subscriptions.borrow_and_update().clone();
+            if tokens.is_empty() {
+                // Nothing to subscribe yet - wait for the monitor to resolve token IDs
+                if subscriptions.changed().await.is_err() {
+                    return; // Feed dropped
+                }
+                continue;
+            }
+
+            match feed.stream(&url, &tokens, &mut subscriptions).await {
+                Ok(resubscribe) => {
+                    backoff = MIN_BACKOFF;
+                    if resubscribe {
+                        eprintln!("🔌 Market feed: token set changed, resubscribing ({} tokens)", subscriptions.borrow().len());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("⚠️  Market feed disconnected: {} (reconnecting in {:?}, REST fallback active)", e, backoff);
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+
+            if subscriptions.has_changed().is_err() {
+                return; // Feed dropped
+            }
+        }
+    }
+
+    /// Run one connection until it fails (Err) or the token set changes (Ok(true)).
+    async fn stream(
+        &self,
+        url: &str,
+        tokens: &[String],
+        subscriptions: &mut watch::Receiver<Vec<String>>,
+    ) -> anyhow::Result<bool> {
+        let (socket, _) = connect_async(url).await?;
+        let (mut write, mut read) = socket.split();
+
+        let subscribe = json!({ "assets_ids": tokens, "type": "market" });
+        write.send(Message::Text(subscribe.to_string())).await?;
+
+        self.books.write().map_err(|_| anyhow::anyhow!("book lock poisoned"))?.clear();
+        self.connected.store(true, Ordering::Relaxed);
+        eprintln!("🔌 Market feed connected ({} tokens)", tokens.len());
+
+        let mut ping = tokio::time::interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+        let mut last_frame = Instant::now();
+        let result = loop {
+            tokio::select! {
+                msg = read.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        last_frame = Instant::now();
+                        self.handle_text(&text);
+                    }
+                    Some(Ok(Message::Ping(payload))) => {
+                        last_frame = Instant::now();
+                        if let Err(e) = write.send(Message::Pong(payload)).await {
+                            break Err(e.into());
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break Err(anyhow::anyhow!("connection closed by server")),
+                    Some(Ok(_)) => last_frame = Instant::now(),
+                    Some(Err(e)) => break Err(e.into()),
+                },
+                _ = ping.tick() => {
+                    // An open socket that stopped sending would otherwise keep serving frozen books
+                    if last_frame.elapsed() >= SILENCE_TIMEOUT {
+                        break Err(anyhow::anyhow!("no frames for {:?}", last_frame.elapsed()));
+                    }
+                    if let Err(e) = write.send(Message::Text("PING".to_string())).await {
+                        break Err(e.into());
+                    }
+                }
+                changed = subscriptions.changed() => {
+                    break match changed {
+                        Ok(()) => Ok(true),
+                        Err(_) => Ok(false),
+                    };
+                }
+            }
+        };
+
+        self.connected.store(false, Ordering::Relaxed);
+        let _ = write.close().await;
+        result
+    }
+
+    /// Apply one text frame (a single event or an array of events) to the local books
+    fn handle_text(&self, text: &str) {
+        if text == "PONG" {
+            return;
+        }
+        let value: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        let events = match value {
+            Value::Array(events) => events,
+            event => vec![event],
+        };
+
+        let mut changed = false;
+        if let Ok(mut books) = self.books.write() {
+            for event in &events {
+                changed |= Self::apply_event(&mut books, event);
+            }
+        }
+        if changed {
+            self.updates.notify_waiters();
+        }
+    }
+
+    /// Apply a `book` snapshot or `price_change` delta. Returns true if any book changed.
+    fn apply_event(books: &mut HashMap<String, LocalBook>, event: &Value) -> bool {
+        fn decimal(v: Option<&Value>) -> Option<Decimal> {
+            match v? {
+                Value::String(s) => Decimal::from_str(s).ok(),
+                Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+                _ => None,
+            }
+        }
+
+        fn levels(v: Option<&Value>) -> BTreeMap<Decimal, Decimal> {
+            v.and_then(|l| l.as_array())
+                .map(|levels| {
+                    levels
+                        .iter()
+                        .filter_map(|l| Some((decimal(l.get("price"))?, decimal(l.get("size"))?)))
+                        .filter(|(_, size)| !size.is_zero())
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
+        match event.get("event_type").and_then(|t| t.as_str()) {
+            Some("book") => {
+                let Some(asset_id) = event.get("asset_id").and_then(|a| a.as_str()) else {
+                    return false;
+                };
+                let book = LocalBook {
+                    bids: levels(event.get("bids").or_else(|| event.get("buys"))),
+                    asks: levels(event.get("asks").or_else(|| event.get("sells"))),
+                };
+                books.insert(asset_id.to_string(), book);
+                true
+            }
+            Some("price_change") => {
+                // Current format: `price_changes: [{asset_id, price, size, side}]`;
+                // legacy format: top-level `asset_id` with `changes: [{price, size, side}]`.
+                let top_asset = event.get("asset_id").and_then(|a| a.as_str());
+                let changes = event
+                    .get("price_changes")
+                    .or_else(|| event.get("changes"))
+                    .and_then(|c| c.as_array());
+
+                let mut changed = false;
+                for change in changes.into_iter().flatten() {
+                    let asset_id = change.get("asset_id").and_then(|a| a.as_str()).or(top_asset);
+                    let (Some(asset_id), Some(price), Some(side)) = (
+                        asset_id,
+                        decimal(change.get("price")),
+                        change.get("side").and_then(|s| s.as_str()),
+                    ) else {
+                        continue;
+                    };
+                    // Only apply deltas on top of a snapshot we already hold
+                    if let Some(book) = books.get_mut(asset_id) {
+                        let size = decimal(change.get("size")).unwrap_or(Decimal::ZERO);
+                        book.set_level(side, price, size);
+                        changed = true;
+                    }
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+}
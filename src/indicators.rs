@@ -1,7 +1,25 @@
-// Technical indicators: RSI, MACD, Momentum
+// Technical indicators: RSI, MACD, Momentum, moving averages, bands, oscillators and volatility
 
+use crate::conditions::ConditionIndicator;
+use crate::config::{IndexType, StrategyConfig};
 use std::collections::VecDeque;
 
+/// A rolling indicator over one price series
+pub trait Indicator: Send + Sync {
+    /// Add the next price
+    fn update(&mut self, price: f64);
+    /// Current value (None until ready)
+    fn value(&self) -> Option<f64>;
+    /// Check if we have enough data
+    fn is_ready(&self) -> bool;
+    /// Drop all state (e.g. at market rollover)
+    fn reset(&mut self);
+    /// Signal line, for indicators that have one (MACD)
+    fn signal(&self) -> Option<f64> {
+        None
+    }
+}
+
 /// Rolling RSI calculator using VecDeque for efficient updates
 pub struct RollingRSI {
     period: usize,
@@ -289,7 +307,515 @@ impl RollingMomentum {
 
     /// Check if we have enough data
     pub fn is_ready(&self) -> bool {
-        self.prices.len() >= self.period + 1
+        self.prices.len() > self.period
+    }
+}
+
+impl Indicator for RollingRSI {
+    fn update(&mut self, price: f64) {
+        self.add_price(price);
+    }
+    fn value(&self) -> Option<f64> {
+        self.get_rsi()
+    }
+    fn is_ready(&self) -> bool {
+        RollingRSI::is_ready(self)
+    }
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+impl Indicator for RollingMACD {
+    fn update(&mut self, price: f64) {
+        self.add_price(price);
+    }
+    fn value(&self) -> Option<f64> {
+        self.get_macd()
+    }
+    fn is_ready(&self) -> bool {
+        RollingMACD::is_ready(self)
+    }
+    fn reset(&mut self) {
+        *self = match self.signal_period {
+            Some(signal_period) => Self::new_with_signal(self.fast_period, self.slow_period, signal_period),
+            None => Self::new(self.fast_period, self.slow_period),
+        };
+    }
+    fn signal(&self) -> Option<f64> {
+        self.get_signal_line()
+    }
+}
+
+impl Indicator for RollingMomentum {
+    fn update(&mut self, price: f64) {
+        self.add_price(price);
+    }
+    fn value(&self) -> Option<f64> {
+        self.get_momentum()
+    }
+    fn is_ready(&self) -> bool {
+        RollingMomentum::is_ready(self)
+    }
+    fn reset(&mut self) {
+        self.prices.clear();
+    }
+}
+
+/// Fixed-size window of the most recent prices
+#[derive(Debug, Clone)]
+struct PriceWindow {
+    period: usize,
+    prices: VecDeque<f64>,
+}
+
+impl PriceWindow {
+    fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            prices: VecDeque::with_capacity(period + 1),
+        }
+    }
+
+    /// Push a price, returning the one that fell out of the window
+    fn push(&mut self, price: f64) -> Option<f64> {
+        self.prices.push_back(price);
+        if self.prices.len() > self.period {
+            self.prices.pop_front()
+        } else {
+            None
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.prices.len() == self.period
+    }
+
+    fn last(&self) -> Option<f64> {
+        self.prices.back().copied()
+    }
+
+    fn high_low(&self) -> (f64, f64) {
+        self.prices
+            .iter()
+            .fold((f64::MIN, f64::MAX), |(hi, lo), &p| (hi.max(p), lo.min(p)))
+    }
+
+    fn clear(&mut self) {
+        self.prices.clear();
+    }
+}
+
+/// Running mean and population standard deviation over a window (O(1) updates)
+#[derive(Debug, Clone)]
+struct RollingStats {
+    window: PriceWindow,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl RollingStats {
+    fn new(period: usize) -> Self {
+        Self {
+            window: PriceWindow::new(period),
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.sum += value;
+        self.sum_sq += value * value;
+        if let Some(old) = self.window.push(value) {
+            self.sum -= old;
+            self.sum_sq -= old * old;
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.window.prices.len().max(1) as f64
+    }
+
+    fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        // Clamp: float cancellation can push a flat window's variance slightly below zero
+        (self.sum_sq / self.window.prices.len().max(1) as f64 - mean * mean).max(0.0).sqrt()
+    }
+
+    fn clear(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+    }
+}
+
+/// Simple moving average
+pub struct Sma {
+    stats: RollingStats,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self { stats: RollingStats::new(period) }
+    }
+}
+
+impl Indicator for Sma {
+    fn update(&mut self, price: f64) {
+        self.stats.push(price);
+    }
+    fn value(&self) -> Option<f64> {
+        self.is_ready().then(|| self.stats.mean())
+    }
+    fn is_ready(&self) -> bool {
+        self.stats.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.stats.clear();
+    }
+}
+
+/// Exponential moving average, seeded with the SMA of the first `period` prices
+pub struct Ema {
+    period: usize,
+    seed: Vec<f64>,
+    ema: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            seed: Vec::new(),
+            ema: None,
+        }
+    }
+}
+
+impl Indicator for Ema {
+    fn update(&mut self, price: f64) {
+        match self.ema {
+            Some(ema) => {
+                let alpha = 2.0 / (self.period as f64 + 1.0);
+                self.ema = Some(price * alpha + ema * (1.0 - alpha));
+            }
+            None => {
+                self.seed.push(price);
+                if self.seed.len() == self.period {
+                    self.ema = Some(self.seed.iter().sum::<f64>() / self.period as f64);
+                    self.seed.clear();
+                }
+            }
+        }
+    }
+    fn value(&self) -> Option<f64> {
+        self.ema
+    }
+    fn is_ready(&self) -> bool {
+        self.ema.is_some()
+    }
+    fn reset(&mut self) {
+        self.seed.clear();
+        self.ema = None;
+    }
+}
+
+/// Bollinger %B: where the price sits within the bands (0 = lower band, 1 = upper band)
+pub struct BollingerPercentB {
+    stats: RollingStats,
+    std_devs: f64,
+}
+
+impl BollingerPercentB {
+    pub fn new(period: usize, std_devs: f64) -> Self {
+        Self {
+            stats: RollingStats::new(period),
+            std_devs,
+        }
+    }
+}
+
+impl Indicator for BollingerPercentB {
+    fn update(&mut self, price: f64) {
+        self.stats.push(price);
+    }
+    fn value(&self) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let price = self.stats.window.last()?;
+        let width = 2.0 * self.std_devs * self.stats.std_dev();
+        if width == 0.0 {
+            return Some(0.5); // Flat window: price is on the middle band
+        }
+        let lower = self.stats.mean() - width / 2.0;
+        Some((price - lower) / width)
+    }
+    fn is_ready(&self) -> bool {
+        self.stats.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.stats.clear();
+    }
+}
+
+/// Stochastic %K (0-100): close relative to the high/low range of the window
+pub struct Stochastic {
+    window: PriceWindow,
+}
+
+impl Stochastic {
+    pub fn new(period: usize) -> Self {
+        Self { window: PriceWindow::new(period) }
+    }
+}
+
+impl Indicator for Stochastic {
+    fn update(&mut self, price: f64) {
+        self.window.push(price);
+    }
+    fn value(&self) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let (high, low) = self.window.high_low();
+        let close = self.window.last()?;
+        if high == low {
+            return Some(50.0);
+        }
+        Some((close - low) / (high - low) * 100.0)
+    }
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// Williams %R (-100..0): distance of the close below the window high
+pub struct WilliamsR {
+    window: PriceWindow,
+}
+
+impl WilliamsR {
+    pub fn new(period: usize) -> Self {
+        Self { window: PriceWindow::new(period) }
+    }
+}
+
+impl Indicator for WilliamsR {
+    fn update(&mut self, price: f64) {
+        self.window.push(price);
+    }
+    fn value(&self) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let (high, low) = self.window.high_low();
+        let close = self.window.last()?;
+        if high == low {
+            return Some(-50.0);
+        }
+        Some((high - close) / (high - low) * -100.0)
+    }
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// Rate of change (%) over `period` prices
+pub struct RateOfChange {
+    window: PriceWindow,
+}
+
+impl RateOfChange {
+    pub fn new(period: usize) -> Self {
+        Self { window: PriceWindow::new(period + 1) }
+    }
+}
+
+impl Indicator for RateOfChange {
+    fn update(&mut self, price: f64) {
+        self.window.push(price);
+    }
+    fn value(&self) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let past = *self.window.prices.front()?;
+        let current = self.window.last()?;
+        if past == 0.0 {
+            return None;
+        }
+        Some((current - past) / past * 100.0)
+    }
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// Realized volatility: standard deviation (%) of tick-to-tick log returns over the window
+pub struct RealizedVolatility {
+    returns: RollingStats,
+    last_price: Option<f64>,
+}
+
+impl RealizedVolatility {
+    pub fn new(period: usize) -> Self {
+        Self {
+            returns: RollingStats::new(period),
+            last_price: None,
+        }
+    }
+}
+
+impl Indicator for RealizedVolatility {
+    fn update(&mut self, price: f64) {
+        if let Some(prev) = self.last_price {
+            // Log returns are undefined at zero; a missing quote doesn't count as a move
+            if prev > 0.0 && price > 0.0 {
+                self.returns.push((price / prev).ln());
+            }
+        }
+        if price > 0.0 {
+            self.last_price = Some(price);
+        }
+    }
+    fn value(&self) -> Option<f64> {
+        self.is_ready().then(|| self.returns.std_dev() * 100.0)
+    }
+    fn is_ready(&self) -> bool {
+        self.returns.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.returns.clear();
+        self.last_price = None;
+    }
+}
+
+/// Z-score of the latest price against the window mean and standard deviation
+pub struct ZScore {
+    stats: RollingStats,
+}
+
+impl ZScore {
+    pub fn new(period: usize) -> Self {
+        Self { stats: RollingStats::new(period) }
+    }
+}
+
+impl Indicator for ZScore {
+    fn update(&mut self, price: f64) {
+        self.stats.push(price);
+    }
+    fn value(&self) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let std_dev = self.stats.std_dev();
+        if std_dev == 0.0 {
+            return Some(0.0);
+        }
+        Some((self.stats.window.last()? - self.stats.mean()) / std_dev)
+    }
+    fn is_ready(&self) -> bool {
+        self.stats.window.is_full()
+    }
+    fn reset(&mut self) {
+        self.stats.clear();
+    }
+}
+
+/// Indicator for the configured `index_type` (periods default to `lookback`)
+pub fn build_indicator(cfg: &StrategyConfig) -> Box<dyn Indicator> {
+    build_indicator_for(cfg.index_type, cfg.lookback, cfg)
+}
+
+/// Indicator of any type over a `lookback` window (MACD periods and band width come from `cfg`)
+pub fn build_indicator_for(index_type: IndexType, lookback: usize, cfg: &StrategyConfig) -> Box<dyn Indicator> {
+    (index_type.spec().build)(lookback, cfg)
+}
+
+/// The configured trend index over one token's prices, updated in O(1) per tick
+pub struct TokenIndicators {
+    pub index: Box<dyn Indicator>,
+    /// Leaf indicators of the composite entry condition (if configured)
+    pub conditions: Vec<ConditionIndicator>,
+    /// Strategy-specific indicators (see `Strategy::extra_indicators`)
+    pub extra: Vec<Box<dyn Indicator>>,
+    /// Prices seen since the indicators were created
+    samples: usize,
+    /// Index value and signal line before the latest price (for slope and crossover checks)
+    previous: (Option<f64>, Option<f64>),
+}
+
+impl TokenIndicators {
+    pub fn new(cfg: &StrategyConfig) -> Self {
+        Self {
+            index: build_indicator(cfg),
+            conditions: cfg
+                .entry_condition
+                .as_ref()
+                .map(|c| c.build_indicators(cfg))
+                .unwrap_or_default(),
+            extra: Vec::new(),
+            samples: 0,
+            previous: (None, None),
+        }
+    }
+
+    pub fn add_price(&mut self, price: f64) {
+        self.previous = (self.index.value(), self.index.signal());
+        self.index.update(price);
+        for condition in &mut self.conditions {
+            condition.update(price);
+        }
+        for indicator in &mut self.extra {
+            indicator.update(price);
+        }
+        self.samples += 1;
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Index value before the latest price
+    pub fn previous_value(&self) -> Option<f64> {
+        self.previous.0
+    }
+
+    /// Signal line before the latest price
+    pub fn previous_signal(&self) -> Option<f64> {
+        self.previous.1
+    }
+}
+
+/// Indicator state for both outcomes of one market
+pub struct MarketIndicators {
+    pub up: TokenIndicators,
+    pub down: TokenIndicators,
+}
+
+impl MarketIndicators {
+    pub fn new(cfg: &StrategyConfig) -> Self {
+        Self {
+            up: TokenIndicators::new(cfg),
+            down: TokenIndicators::new(cfg),
+        }
+    }
+
+    pub fn add_prices(&mut self, up_price: f64, down_price: f64) {
+        self.up.add_price(up_price);
+        self.down.add_price(down_price);
     }
 }
 
@@ -4,8 +4,18 @@
 pub mod types;
 pub mod config;
 pub mod indicators;
+pub mod conditions;
 pub mod strategies;
 pub mod monitor;
+pub mod engine;
+pub mod feed;
+pub mod backtest;
+pub mod recorder;
+pub mod session;
+pub mod orders;
+pub mod replay;
+pub mod sweep;
+pub mod walkforward;
 pub mod api;
 pub mod models;
 pub mod simulation;
@@ -23,9 +33,11 @@ pub use models::*;
 // Global history.toml logger (mirrors polymarket-trading-bot design)
 use std::fs::File;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, OnceLock};
 
 static HISTORY_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+static EVENTS_MUTED: AtomicBool = AtomicBool::new(false);
 
 /// Initialize the global history file writer (called by main.rs)
 pub fn init_history_file(file: File) {
@@ -52,6 +64,33 @@ pub fn log_trading_event(event: &str) {
     log_to_history(&format!("[{}] {}\n", timestamp, event));
 }
 
+/// Print a trading event and append it to history.toml (unless events are muted)
+pub fn log_event(msg: &str) {
+    if events_muted() {
+        return;
+    }
+    println!("{}", msg);
+    log_trading_event(msg);
+}
+
+/// Silence `log_event` for the rest of the process (batch backtests print only their results)
+pub fn mute_events() {
+    EVENTS_MUTED.store(true, Ordering::Relaxed);
+}
+
+pub fn events_muted() -> bool {
+    EVENTS_MUTED.load(Ordering::Relaxed)
+}
+
+/// Shorten a long ID (token, order or trade ID) to its prefix and suffix for logs
+pub fn short_id(id: &str) -> String {
+    if id.len() <= 12 {
+        id.to_string()
+    } else {
+        format!("{}...{}", &id[..6], &id[id.len() - 6..])
+    }
+}
+
 /// Macro to log to both stderr and history.toml (like println! but persisted)
 #[macro_export]
 macro_rules! log_println {
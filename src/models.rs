@@ -28,20 +28,127 @@ pub struct Token {
     pub price: Option<Decimal>,
 }
 
-#[derive(Debug, Clone)]
+/// One price level of an order book
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// CLOB /book response (levels are re-sorted best-first by the API client)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub asset_id: String,
+    #[serde(default)]
+    pub bids: Vec<BookLevel>,
+    #[serde(default)]
+    pub asks: Vec<BookLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPrice {
     pub token_id: String,
     pub bid: Option<Decimal>,
     pub ask: Option<Decimal>,
+    /// Bid levels, best (highest) first. Empty when only top-of-book is known.
+    #[serde(default)]
+    pub bids: Vec<BookLevel>,
+    /// Ask levels, best (lowest) first. Empty when only top-of-book is known.
+    #[serde(default)]
+    pub asks: Vec<BookLevel>,
 }
 
 impl TokenPrice {
     pub fn ask_price(&self) -> Decimal {
         self.ask.unwrap_or(Decimal::ZERO)
     }
+
+    /// Midpoint of the best bid and ask (None unless both sides are quoted)
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.bid? + self.ask?) / Decimal::TWO)
+    }
+
+    /// Whether depth levels are available (as opposed to top-of-book only)
+    pub fn has_depth(&self) -> bool {
+        !self.bids.is_empty() || !self.asks.is_empty()
+    }
+
+    /// Shares offered at or below `max_price` (what a BUY limit at that price can take)
+    pub fn ask_depth(&self, max_price: Decimal) -> Decimal {
+        self.asks
+            .iter()
+            .take_while(|l| l.price <= max_price)
+            .map(|l| l.size)
+            .sum()
+    }
+
+    /// Shares bid at or above `min_price` (what a SELL limit at that price can take)
+    pub fn bid_depth(&self, min_price: Decimal) -> Decimal {
+        self.bids
+            .iter()
+            .take_while(|l| l.price >= min_price)
+            .map(|l| l.size)
+            .sum()
+    }
+
+    /// Shares of a `shares` BUY limit at `limit_price` the book can fill right now.
+    /// Returns `shares` unchanged when no depth is known (top-of-book only).
+    pub fn fillable_buy(&self, shares: Decimal, limit_price: Decimal) -> Decimal {
+        if self.asks.is_empty() {
+            return shares;
+        }
+        // The limit is derived from the best ask, so never cap below the best level
+        let limit = limit_price.max(self.ask_price());
+        shares.min(self.ask_depth(limit))
+    }
+
+    /// Average fill price for buying `shares` by walking the asks (None if the book is too thin)
+    pub fn buy_vwap(&self, shares: Decimal) -> Option<Decimal> {
+        Self::vwap(&self.asks, shares)
+    }
+
+    /// Average fill price for selling `shares` by walking the bids (None if the book is too thin)
+    pub fn sell_vwap(&self, shares: Decimal) -> Option<Decimal> {
+        Self::vwap(&self.bids, shares)
+    }
+
+    /// Slippage of a `shares` market buy versus the best ask
+    pub fn buy_slippage(&self, shares: Decimal) -> Option<Decimal> {
+        Some(self.buy_vwap(shares)? - self.ask?)
+    }
+
+    /// Order book imbalance over the top `levels`: (bid size − ask size) / total, in [-1, 1].
+    /// Positive values mean more resting buy interest.
+    pub fn imbalance(&self, levels: usize) -> Option<f64> {
+        use rust_decimal::prelude::ToPrimitive;
+        let bid_size: Decimal = self.bids.iter().take(levels).map(|l| l.size).sum();
+        let ask_size: Decimal = self.asks.iter().take(levels).map(|l| l.size).sum();
+        let total = bid_size + ask_size;
+        if total.is_zero() {
+            return None;
+        }
+        ((bid_size - ask_size) / total).to_f64()
+    }
+
+    fn vwap(levels: &[BookLevel], shares: Decimal) -> Option<Decimal> {
+        if shares <= Decimal::ZERO {
+            return None;
+        }
+        let mut remaining = shares;
+        let mut cost = Decimal::ZERO;
+        for level in levels {
+            let take = remaining.min(level.size);
+            cost += take * level.price;
+            remaining -= take;
+            if remaining.is_zero() {
+                return Some(cost / shares);
+            }
+        }
+        None
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketData {
     pub condition_id: String,
     pub market_name: String,
@@ -77,6 +184,30 @@ pub struct OrderRequest {
     pub order_type: String, // "LIMIT" or "MARKET"
 }
 
+/// Order resting on the CLOB (from the authenticated open-orders endpoint)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub order_id: String,
+    pub token_id: String,
+    pub side: String, // "BUY" or "SELL"
+    pub price: Decimal,
+    pub original_size: Decimal,
+    pub size_matched: Decimal,
+}
+
+/// One of our orders matched in a CLOB trade (from the authenticated trades endpoint)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub token_id: String,
+    pub side: String, // "BUY" or "SELL"
+    pub price: Decimal,
+    pub size: Decimal,
+    /// Match time (UNIX seconds)
+    pub matched_at: u64,
+}
+
 /// Order response from Polymarket CLOB
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderResponse {
@@ -1,58 +1,135 @@
 // Market monitoring for real-time price data
 
 use crate::api::PolymarketApi;
+use crate::config::AssetConfig;
+use crate::feed::MarketFeed;
 use crate::models::{Market, MarketData, TokenPrice};
+use crate::recorder::SnapshotRecorder;
 use anyhow::Result;
-use std::sync::Arc;
-use tokio::time::Duration;
 use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct MarketSnapshot {
-    pub eth_market: MarketData,
-    pub btc_market: MarketData,
-    pub solana_market: MarketData,
-    pub xrp_market: MarketData,
+    /// Market data per enabled asset, keyed by registry symbol ("ETH", "BTC", ...)
+    pub markets: BTreeMap<String, MarketData>,
     pub timestamp: std::time::Instant,
+    /// Wall-clock capture time (UNIX milliseconds)
+    pub captured_at_ms: u64,
     pub time_remaining_seconds: u64,
     pub period_timestamp: u64,
+    /// Known resolution per asset (1 = Up won, 0 = Down won), e.g. from backtest data; empty live
+    pub outcomes: BTreeMap<String, u8>,
+}
+
+impl MarketSnapshot {
+    /// Look up the market data for an asset symbol
+    pub fn market_for(&self, asset: &str) -> Option<&MarketData> {
+        self.markets.get(asset)
+    }
+}
+
+/// Where traders get market snapshots from: the live monitor or a recorded replay.
+///
+/// Traders take all time-dependent decisions from `MarketSnapshot::captured_at_ms`
+/// (the source's clock), so a replay reproduces a live session's decisions exactly.
+pub trait MarketDataSource: Send + Sync {
+    /// Next snapshot, or `Ok(None)` once the source is exhausted (end of a replay)
+    fn next_snapshot(&self) -> impl std::future::Future<Output = Result<Option<MarketSnapshot>>> + Send;
+
+    /// Wait before asking for the next snapshot
+    fn wait(&self, poll_interval: std::time::Duration) -> impl std::future::Future<Output = ()> + Send;
+
+    /// Pause the trading loop (e.g. the post-entry confirmation delay)
+    fn pause(&self, duration: std::time::Duration) -> impl std::future::Future<Output = ()> + Send;
+}
+
+impl MarketDataSource for MarketMonitor {
+    async fn next_snapshot(&self) -> Result<Option<MarketSnapshot>> {
+        self.fetch_market_data().await.map(Some)
+    }
+
+    async fn wait(&self, poll_interval: std::time::Duration) {
+        self.wait_for_update(poll_interval).await
+    }
+
+    async fn pause(&self, duration: std::time::Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
+
+/// Runtime state for one registered asset (current market + CLOB token IDs)
+pub struct TrackedAsset {
+    config: AssetConfig,
+    market: tokio::sync::Mutex<Option<Market>>,
+    up_token_id: tokio::sync::Mutex<Option<String>>,
+    down_token_id: tokio::sync::Mutex<Option<String>>,
 }
 
+impl TrackedAsset {
+    fn new(config: AssetConfig) -> Self {
+        Self {
+            config,
+            market: tokio::sync::Mutex::new(None),
+            up_token_id: tokio::sync::Mutex::new(None),
+            down_token_id: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.config.symbol
+    }
+
+    /// Currently tracked market (None until discovered)
+    pub async fn market(&self) -> Option<Market> {
+        self.market.lock().await.clone()
+    }
+}
+
+/// Keyed registry of assets (symbol/alias → market, token IDs, enable flag, slug prefixes)
+pub struct AssetRegistry {
+    assets: Vec<TrackedAsset>,
+}
+
+impl AssetRegistry {
+    pub fn new(configs: Vec<AssetConfig>) -> Self {
+        Self {
+            assets: configs.into_iter().map(TrackedAsset::new).collect(),
+        }
+    }
+
+    /// Resolve an asset symbol or alias to its tracked state
+    pub fn get(&self, name: &str) -> Option<&TrackedAsset> {
+        self.assets.iter().find(|a| a.config.matches(name))
+    }
+
+    /// Enabled assets in registry order
+    pub fn enabled(&self) -> impl Iterator<Item = &TrackedAsset> {
+        self.assets.iter().filter(|a| a.config.enabled)
+    }
+}
+
+/// Default number of book levels per side exposed on `TokenPrice`
+pub const DEFAULT_BOOK_DEPTH: usize = 10;
+
 pub struct MarketMonitor {
     api: Arc<PolymarketApi>,
-    eth_market: Arc<tokio::sync::Mutex<Market>>,
-    btc_market: Arc<tokio::sync::Mutex<Market>>,
-    solana_market: Arc<tokio::sync::Mutex<Market>>,
-    xrp_market: Arc<tokio::sync::Mutex<Market>>,
-    eth_up_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    eth_down_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    btc_up_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    btc_down_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    solana_up_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    solana_down_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    xrp_up_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    xrp_down_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    // Logging + enable flags
-    enable_eth: bool,
-    enable_solana: bool,
-    enable_xrp: bool,
-    eth_tokens_logged: Arc<tokio::sync::Mutex<bool>>,
-    btc_tokens_logged: Arc<tokio::sync::Mutex<bool>>,
+    registry: AssetRegistry,
+    /// Streaming market channel; REST `/price` is used when absent or disconnected
+    feed: Option<Arc<MarketFeed>>,
+    /// Minimum spacing between feed-driven wakeups (coalesces bursts of book updates)
+    min_update_interval: std::time::Duration,
+    /// Order book levels kept per side on each TokenPrice
+    book_depth: usize,
+    /// Persists every snapshot for later replay (disabled when None)
+    recorder: Option<SnapshotRecorder>,
     /// Tracks which 15‑minute period we are currently trading (UNIX timestamp rounded to 900s)
     current_period_timestamp: Arc<tokio::sync::Mutex<u64>>,
 }
 
 impl MarketMonitor {
-    pub fn new(
-        api: Arc<PolymarketApi>,
-        eth_market: Market,
-        btc_market: Market,
-        solana_market: Market,
-        xrp_market: Market,
-        enable_eth: bool,
-        enable_solana: bool,
-        enable_xrp: bool,
-    ) -> Result<Self> {
+    pub fn new(api: Arc<PolymarketApi>, assets: Vec<AssetConfig>) -> Result<Self> {
         // Compute current 15‑minute period like polymarket‑trading‑bot
         let current_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
@@ -61,43 +138,83 @@ impl MarketMonitor {
 
         Ok(Self {
             api,
-            eth_market: Arc::new(tokio::sync::Mutex::new(eth_market)),
-            btc_market: Arc::new(tokio::sync::Mutex::new(btc_market)),
-            solana_market: Arc::new(tokio::sync::Mutex::new(solana_market)),
-            xrp_market: Arc::new(tokio::sync::Mutex::new(xrp_market)),
-            eth_up_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            eth_down_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            btc_up_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            btc_down_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            solana_up_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            solana_down_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            xrp_up_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            xrp_down_token_id: Arc::new(tokio::sync::Mutex::new(None)),
-            enable_eth,
-            enable_solana,
-            enable_xrp,
-            eth_tokens_logged: Arc::new(tokio::sync::Mutex::new(false)),
-            btc_tokens_logged: Arc::new(tokio::sync::Mutex::new(false)),
+            registry: AssetRegistry::new(assets),
+            feed: None,
+            min_update_interval: std::time::Duration::ZERO,
+            book_depth: DEFAULT_BOOK_DEPTH,
+            recorder: None,
             current_period_timestamp: Arc::new(tokio::sync::Mutex::new(current_period)),
         })
     }
 
+    /// Stream prices from the CLOB market channel instead of polling REST.
+    /// Feed-driven wakeups are spaced at least `min_update_interval` apart.
+    pub fn with_feed(mut self, feed: Arc<MarketFeed>, min_update_interval: std::time::Duration) -> Self {
+        self.feed = Some(feed);
+        self.min_update_interval = min_update_interval;
+        self
+    }
+
+    /// Number of order book levels to keep per side (default 10)
+    pub fn with_book_depth(mut self, depth: usize) -> Self {
+        self.book_depth = depth.max(1);
+        self
+    }
+
+    /// Record every fetched snapshot to disk
+    pub fn with_recorder(mut self, recorder: SnapshotRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Wait for the next tick: the next book update when the feed is connected,
+    /// otherwise the full polling interval.
+    pub async fn wait_for_update(&self, poll_interval: std::time::Duration) {
+        match &self.feed {
+            Some(feed) if feed.is_connected() => {
+                tokio::time::sleep(self.min_update_interval).await;
+                feed.wait_for_update(poll_interval.saturating_sub(self.min_update_interval)).await;
+            }
+            _ => tokio::time::sleep(poll_interval).await,
+        }
+    }
+
+    /// Asset registry backing this monitor
+    pub fn registry(&self) -> &AssetRegistry {
+        &self.registry
+    }
+
+    /// Discover the current market for every enabled asset (called once at startup).
+    pub async fn discover_markets(&self) -> Result<()> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+
+        for asset in self.registry.enabled() {
+            let market = self.discover_market_for(&asset.config, now).await?;
+            *asset.market.lock().await = Some(market);
+        }
+
+        *self.current_period_timestamp.lock().await = Self::current_period(now);
+        Ok(())
+    }
+
     /// Helper: round current UNIX timestamp down to the nearest 15‑minute period
     fn current_period(now: u64) -> u64 {
         (now / 900) * 900
     }
 
-    /// Discover the active 15‑minute market for a given asset (ETH/BTC) by slug prefixes.
+    /// Discover the active 15‑minute market for a given asset by its slug prefixes.
     ///
-    /// This mirrors the logic in `src/bin/main.rs::discover_market`, but is local to the
-    /// monitor so we can roll over to new markets when each 15‑minute period starts.
+    /// Used both at startup and to roll over to new markets when each 15‑minute period starts.
     async fn discover_market_for(
         &self,
-        market_name: &str,
-        slug_prefixes: &[&str],
+        asset: &AssetConfig,
         current_time: u64,
     ) -> anyhow::Result<Market> {
         let rounded_time = Self::current_period(current_time);
+        let market_name = asset.symbol.as_str();
+        let slug_prefixes = &asset.slug_prefixes;
 
         for (i, prefix) in slug_prefixes.iter().enumerate() {
             if i > 0 {
@@ -141,8 +258,8 @@ impl MarketMonitor {
         )
     }
 
-    /// If the 15‑minute period rolled over, discover the new ETH/BTC markets and reset
-    /// token IDs so that we fetch prices for the new market's tokens instead of the
+    /// If the 15‑minute period rolled over, discover the new markets for every asset and
+    /// reset token IDs so that we fetch prices for the new market's tokens instead of the
     /// previous (now closed) market.
     async fn maybe_roll_to_new_period(&self) -> Result<()> {
         let now = std::time::SystemTime::now()
@@ -158,107 +275,76 @@ impl MarketMonitor {
 
         eprintln!("🔄 Detected new 15‑minute period ({}) – rediscovering markets…", new_period);
 
-        // Discover fresh ETH/BTC markets for the new period.
-        // Even if ETH trading is disabled, we still track its market for completeness.
-        let eth_market = self
-            .discover_market_for("ETH", &["eth"], now)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to discover new ETH market: {}", e))?;
-        let btc_market = self
-            .discover_market_for("BTC", &["btc"], now)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to discover new BTC market: {}", e))?;
-
-        {
-            let mut eth_guard = self.eth_market.lock().await;
-            *eth_guard = eth_market;
-        }
-        {
-            let mut btc_guard = self.btc_market.lock().await;
-            *btc_guard = btc_market;
+        // Discover fresh markets for every enabled asset in the new period.
+        let mut discovered = Vec::new();
+        for asset in self.registry.enabled() {
+            let market = self
+                .discover_market_for(&asset.config, now)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to discover new {} market: {}", asset.symbol(), e))?;
+            discovered.push((asset, market));
         }
 
-        // Reset token IDs so `refresh_tokens` will fetch IDs for the new markets.
-        *self.eth_up_token_id.lock().await = None;
-        *self.eth_down_token_id.lock().await = None;
-        *self.btc_up_token_id.lock().await = None;
-        *self.btc_down_token_id.lock().await = None;
-
-        // Clear "logged" flags so token IDs for the new period are printed once.
-        *self.eth_tokens_logged.lock().await = false;
-        *self.btc_tokens_logged.lock().await = false;
+        for (asset, market) in discovered {
+            *asset.market.lock().await = Some(market);
+            // Reset token IDs so `refresh_tokens` will fetch IDs for the new markets
+            // (and print them once for the new period).
+            *asset.up_token_id.lock().await = None;
+            *asset.down_token_id.lock().await = None;
+        }
 
         *period_lock = new_period;
 
         Ok(())
     }
 
-    /// Refresh token IDs from CLOB market details (Up/Down token IDs)
+    /// Refresh token IDs from CLOB market details (Up/Down token IDs).
+    /// Assets whose IDs are already known for the current period are skipped.
     async fn refresh_tokens(&self) -> Result<()> {
-        // Resolve current condition IDs
-        let eth_condition_id = {
-            let eth_guard = self.eth_market.lock().await;
-            eth_guard.condition_id.clone()
-        };
-        let btc_condition_id = {
-            let btc_guard = self.btc_market.lock().await;
-            btc_guard.condition_id.clone()
-        };
+        for asset in self.registry.enabled() {
+            if asset.up_token_id.lock().await.is_some() && asset.down_token_id.lock().await.is_some() {
+                continue;
+            }
+            let condition_id = match asset.market.lock().await.as_ref() {
+                Some(market) => market.condition_id.clone(),
+                None => continue,
+            };
+            let details = match self.api.get_market_details(&condition_id).await {
+                Ok(details) => details,
+                Err(_) => {
+                    eprintln!("⚠️  Failed to fetch {} market details for condition_id {}", asset.symbol(), condition_id);
+                    continue;
+                }
+            };
 
-        // Fetch ETH market details and extract CLOB token IDs (only if ETH trading enabled)
-        if self.enable_eth && eth_condition_id != "dummy_eth_fallback" {
-            if let Ok(details) = self.api.get_market_details(&eth_condition_id).await {
-                if let Some(tokens) = &details.tokens {
-                    for token in tokens {
-                        let outcome_upper = token.outcome.to_uppercase();
-                        if outcome_upper.contains("UP") || outcome_upper == "1" {
-                            let mut id_lock = self.eth_up_token_id.lock().await;
-                            let first_time = id_lock.is_none();
-                            *id_lock = Some(token.token_id.clone());
-                            if first_time {
-                                eprintln!("ETH Up token_id: {}", token.token_id);
-                            }
-                        } else if outcome_upper.contains("DOWN") || outcome_upper == "0" {
-                            let mut id_lock = self.eth_down_token_id.lock().await;
-                            let first_time = id_lock.is_none();
-                            *id_lock = Some(token.token_id.clone());
-                            if first_time {
-                                eprintln!("ETH Down token_id: {}", token.token_id);
-                            }
-                        }
-                    }
+            for token in details.tokens.iter().flatten() {
+                let outcome_upper = token.outcome.to_uppercase();
+                let (id_slot, label) = if outcome_upper.contains("UP") || outcome_upper == "1" {
+                    (&asset.up_token_id, "Up")
+                } else if outcome_upper.contains("DOWN") || outcome_upper == "0" {
+                    (&asset.down_token_id, "Down")
+                } else {
+                    continue;
+                };
+
+                let mut id_lock = id_slot.lock().await;
+                let first_time = id_lock.is_none();
+                *id_lock = Some(token.token_id.clone());
+                if first_time {
+                    eprintln!("{} {} token_id: {}", asset.symbol(), label, token.token_id);
                 }
-            } else {
-                eprintln!("⚠️  Failed to fetch ETH market details for condition_id {}", eth_condition_id);
             }
         }
 
-        // Fetch BTC market details and extract CLOB token IDs
-        if btc_condition_id != "dummy_btc_fallback" {
-            if let Ok(details) = self.api.get_market_details(&btc_condition_id).await {
-                if let Some(tokens) = &details.tokens {
-                    for token in tokens {
-                        let outcome_upper = token.outcome.to_uppercase();
-                        if outcome_upper.contains("UP") || outcome_upper == "1" {
-                            let mut id_lock = self.btc_up_token_id.lock().await;
-                            let first_time = id_lock.is_none();
-                            *id_lock = Some(token.token_id.clone());
-                            if first_time {
-                                eprintln!("BTC Up token_id: {}", token.token_id);
-                            }
-                        } else if outcome_upper.contains("DOWN") || outcome_upper == "0" {
-                            let mut id_lock = self.btc_down_token_id.lock().await;
-                            let first_time = id_lock.is_none();
-                            *id_lock = Some(token.token_id.clone());
-                            if first_time {
-                                eprintln!("BTC Down token_id: {}", token.token_id);
-                            }
-                        }
-                    }
-                }
-            } else {
-                eprintln!("⚠️  Failed to fetch BTC market details for condition_id {}", btc_condition_id);
+        // Keep the market channel subscribed to the current period's tokens
+        // (the feed resubscribes by itself when this set changes on rollover).
+        if let Some(feed) = &self.feed {
+            let mut token_ids = Vec::new();
+            for asset in self.registry.enabled() {
+                token_ids.extend(asset.up_token_id.lock().await.clone());
+                token_ids.extend(asset.down_token_id.lock().await.clone());
             }
+            feed.subscribe(token_ids);
         }
 
         Ok(())
@@ -274,27 +360,45 @@ impl MarketMonitor {
 
         self.refresh_tokens().await?;
 
-        let current_time = std::time::SystemTime::now()
+        let captured_at_ms = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
-            .as_secs();
+            .as_millis() as u64;
+        let current_time = captured_at_ms / 1000;
         let period_timestamp = (current_time / 900) * 900;
         let time_remaining_seconds = 900 - (current_time % 900);
 
-        // Fetch prices for each market (mirroring polymarket-trading-bot)
-        let eth_up_id = self.eth_up_token_id.lock().await.clone();
-        let eth_down_id = self.eth_down_token_id.lock().await.clone();
-        let btc_up_id = self.btc_up_token_id.lock().await.clone();
-        let btc_down_id = self.btc_down_token_id.lock().await.clone();
-
         async fn fetch_token_price(
             api: &PolymarketApi,
+            feed: Option<&MarketFeed>,
+            depth: usize,
             token_id: &Option<String>,
             market_name: &str,
             outcome: &str,
         ) -> Option<TokenPrice> {
             let token_id = token_id.as_ref()?;
 
+            // Prefer the live book; fall back to REST when the socket is down
+            if let Some(quote) = feed.and_then(|f| f.quote(token_id, depth)) {
+                return Some(quote);
+            }
+
+            // REST /book gives best bid/ask plus depth in one call
+            match api.get_order_book(token_id, depth).await {
+                Ok(book) => {
+                    return Some(TokenPrice {
+                        token_id: token_id.clone(),
+                        bid: book.bids.first().map(|l| l.price),
+                        ask: book.asks.first().map(|l| l.price),
+                        bids: book.bids,
+                        asks: book.asks,
+                    });
+                }
+                Err(e) => {
+                    eprintln!("⚠️  Failed to fetch {} {} order book: {} (falling back to /price)", market_name, outcome, e);
+                }
+            }
+
             // BUY price (bid)
             let buy_price = match api.get_side_price(token_id, "BUY").await {
                 Ok(price) => Some(price),
@@ -318,27 +422,49 @@ impl MarketMonitor {
                     token_id: token_id.clone(),
                     bid: buy_price,
                     ask: sell_price,
+                    bids: Vec::new(),
+                    asks: Vec::new(),
                 })
             } else {
                 None
             }
         }
 
-        // Fetch BTC prices (always enabled)
-        let (btc_up_price, btc_down_price) = tokio::join!(
-            fetch_token_price(&self.api, &btc_up_id, "BTC", "Up"),
-            fetch_token_price(&self.api, &btc_down_id, "BTC", "Down"),
-        );
-
-        // Fetch ETH prices only if enabled
-        let (eth_up_price, eth_down_price) = if self.enable_eth {
-            tokio::join!(
-                fetch_token_price(&self.api, &eth_up_id, "ETH", "Up"),
-                fetch_token_price(&self.api, &eth_down_id, "ETH", "Down"),
-            )
-        } else {
-            (None, None)
-        };
+        /// Fetch Up/Down prices for one asset and build its MarketData
+        async fn fetch_asset_data(
+            api: &PolymarketApi,
+            feed: Option<&MarketFeed>,
+            depth: usize,
+            asset: &TrackedAsset,
+        ) -> Option<MarketData> {
+            let market = asset.market().await?;
+            let up_id = asset.up_token_id.lock().await.clone();
+            let down_id = asset.down_token_id.lock().await.clone();
+            let (up_price, down_price) = tokio::join!(
+                fetch_token_price(api, feed, depth, &up_id, asset.symbol(), "Up"),
+                fetch_token_price(api, feed, depth, &down_id, asset.symbol(), "Down"),
+            );
+
+            Some(MarketData {
+                condition_id: market.condition_id,
+                market_name: market.slug,
+                up_token: up_price,
+                down_token: down_price,
+            })
+        }
+
+        // Fetch prices for each enabled market concurrently (mirroring polymarket-trading-bot)
+        let feed = self.feed.as_deref();
+        let enabled: Vec<&TrackedAsset> = self.registry.enabled().collect();
+        let fetched = futures_util::future::join_all(
+            enabled.iter().map(|asset| fetch_asset_data(&self.api, feed, self.book_depth, asset)),
+        )
+        .await;
+        let markets: BTreeMap<String, MarketData> = enabled
+            .iter()
+            .zip(fetched)
+            .filter_map(|(asset, data)| Some((asset.symbol().to_string(), data?)))
+            .collect();
 
         // --- Compact one-line log similar to polymarket-trading-bot ---
         fn fmt_token_price(tp: &Option<TokenPrice>) -> String {
@@ -357,126 +483,71 @@ impl MarketMonitor {
             format!("{:2}m {:02}s", mins, rem)
         }
 
-        use rust_decimal::Decimal;
-
-        let btc_up_str = fmt_token_price(&btc_up_price);
-        let btc_down_str = fmt_token_price(&btc_down_price);
-        let eth_up_str = fmt_token_price(&eth_up_price);
-        let eth_down_str = fmt_token_price(&eth_down_price);
-        // For now, Solana/XRP are dummy - show N/A-style placeholders (and allow disabling later)
-        let sol_up_str = "$--/--";
-        let sol_down_str = "$--/--";
-        let xrp_up_str = "$--/--";
-        let xrp_down_str = "$--/--";
-
         let time_remaining_str = format_remaining_time(time_remaining_seconds);
 
-        // Build log line conditionally based on enabled assets
-        let mut parts: Vec<String> = Vec::new();
-        parts.push(format!("BTC: U{} D{}", btc_up_str, btc_down_str));
-        if self.enable_eth {
-            parts.push(format!("ETH: U{} D{}", eth_up_str, eth_down_str));
-        }
-        if self.enable_solana {
-            parts.push(format!("SOL: U{} D{}", sol_up_str, sol_down_str));
-        }
-        if self.enable_xrp {
-            parts.push(format!("XRP: U{} D{}", xrp_up_str, xrp_down_str));
-        }
-
-        let price_log_line = format!("📊 {} | ⏱️  {}", parts.join(" | "), time_remaining_str);
+        // Build log line for enabled assets in registry order
+        let parts: Vec<String> = enabled
+            .iter()
+            .filter_map(|asset| {
+                let data = markets.get(asset.symbol())?;
+                Some(format!(
+                    "{}: U{} D{}",
+                    asset.symbol(),
+                    fmt_token_price(&data.up_token),
+                    fmt_token_price(&data.down_token)
+                ))
+            })
+            .collect();
+
+        let source = match &self.feed {
+            Some(feed) if feed.is_connected() => "WS",
+            _ => "REST",
+        };
+        let price_log_line = format!("📊 {} | ⏱️  {} [{}]", parts.join(" | "), time_remaining_str, source);
         // Print to stdout so it's visible just like in polymarket-trading-bot
         println!("{}", price_log_line);
         // Also persist to history.toml
         crate::log_trading_event(&price_log_line);
 
-        let eth_market_guard = self.eth_market.lock().await;
-        let eth_market_data = MarketData {
-            condition_id: eth_market_guard.condition_id.clone(),
-            market_name: eth_market_guard.slug.clone(),
-            up_token: eth_up_price,
-            down_token: eth_down_price,
-        };
-        drop(eth_market_guard);
-
-        let btc_market_guard = self.btc_market.lock().await;
-        let btc_market_data = MarketData {
-            condition_id: btc_market_guard.condition_id.clone(),
-            market_name: btc_market_guard.slug.clone(),
-            up_token: btc_up_price,
-            down_token: btc_down_price,
-        };
-        drop(btc_market_guard);
-
-        // Dummy data for Solana and XRP (can be enhanced later)
-        let solana_market_data = MarketData {
-            condition_id: "dummy".to_string(),
-            market_name: "solana-updown-15m".to_string(),
-            up_token: None,
-            down_token: None,
-        };
-
-        let xrp_market_data = MarketData {
-            condition_id: "dummy".to_string(),
-            market_name: "xrp-updown-15m".to_string(),
-            up_token: None,
-            down_token: None,
-        };
-
-        Ok(MarketSnapshot {
-            eth_market: eth_market_data,
-            btc_market: btc_market_data,
-            solana_market: solana_market_data,
-            xrp_market: xrp_market_data,
+        let snapshot = MarketSnapshot {
+            markets,
             timestamp: std::time::Instant::now(),
+            captured_at_ms,
             time_remaining_seconds,
             period_timestamp,
-        })
+            outcomes: BTreeMap::new(),
+        };
+
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = recorder.record(&snapshot) {
+                eprintln!("⚠️  Failed to record snapshot: {}", e);
+            }
+        }
+
+        Ok(snapshot)
     }
 
     /// Get Up token ID for an asset
     pub async fn get_up_token_id(&self, asset: &str) -> anyhow::Result<String> {
-        match asset {
-            "BTC" => {
-                let guard = self.btc_up_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("BTC Up token ID not available. Market may not be initialized."))
-            }
-            "ETH" => {
-                let guard = self.eth_up_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("ETH Up token ID not available. Market may not be initialized."))
-            }
-            "SOL" | "Solana" => {
-                let guard = self.solana_up_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("Solana Up token ID not available. Market may not be initialized."))
-            }
-            "XRP" => {
-                let guard = self.xrp_up_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("XRP Up token ID not available. Market may not be initialized."))
-            }
-            _ => anyhow::bail!("Unsupported asset: {}", asset),
-        }
+        let tracked = self
+            .registry
+            .get(asset)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported asset: {}", asset))?;
+        let guard = tracked.up_token_id.lock().await;
+        guard.clone().ok_or_else(|| {
+            anyhow::anyhow!("{} Up token ID not available. Market may not be initialized.", tracked.symbol())
+        })
     }
 
     /// Get Down token ID for an asset
     pub async fn get_down_token_id(&self, asset: &str) -> anyhow::Result<String> {
-        match asset {
-            "BTC" => {
-                let guard = self.btc_down_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("BTC Down token ID not available. Market may not be initialized."))
-            }
-            "ETH" => {
-                let guard = self.eth_down_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("ETH Down token ID not available. Market may not be initialized."))
-            }
-            "SOL" | "Solana" => {
-                let guard = self.solana_down_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("Solana Down token ID not available. Market may not be initialized."))
-            }
-            "XRP" => {
-                let guard = self.xrp_down_token_id.lock().await;
-                guard.clone().ok_or_else(|| anyhow::anyhow!("XRP Down token ID not available. Market may not be initialized."))
-            }
-            _ => anyhow::bail!("Unsupported asset: {}", asset),
-        }
+        let tracked = self
+            .registry
+            .get(asset)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported asset: {}", asset))?;
+        let guard = tracked.down_token_id.lock().await;
+        guard.clone().ok_or_else(|| {
+            anyhow::anyhow!("{} Down token ID not available. Market may not be initialized.", tracked.symbol())
+        })
     }
 }
@@ -0,0 +1,337 @@
+// Order lifecycle - every live order from submission to its final state, with timestamps and fills
+//
+// New → Acked → PartiallyFilled → Filled / Cancelled / Rejected / Expired.
+// Each transition and fill is logged with its reason, and the full history of every order is
+// summarized when its market ends, so the trading log shows what the bot did and why.
+
+use crate::engine::OrderIntent;
+use crate::models::OrderFill;
+use crate::short_id;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use tracing::info;
+
+/// Unfilled remainder (shares) below which an order counts as fully filled
+const FILLED_DUST: Decimal = dec!(0.01);
+
+/// Local handle of a managed order (assigned before the venue returns an order ID)
+pub type OrderKey = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// Created locally, not yet accepted by the venue
+    New,
+    /// Accepted and resting (or matching) on the book
+    Acked,
+    PartiallyFilled,
+    Filled,
+    /// Cancelled by us before it filled completely
+    Cancelled,
+    /// Refused by the venue
+    Rejected,
+    /// Cancelled because its market ended
+    Expired,
+}
+
+impl OrderStatus {
+    pub fn is_final(self) -> bool {
+        matches!(self, Self::Filled | Self::Cancelled | Self::Rejected | Self::Expired)
+    }
+
+    /// Whether the order may still fill
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Acked | Self::PartiallyFilled)
+    }
+
+    fn can_move_to(self, next: Self) -> bool {
+        match self {
+            Self::New => matches!(next, Self::Acked | Self::Rejected | Self::Cancelled),
+            Self::Acked => matches!(next, Self::PartiallyFilled | Self::Filled | Self::Cancelled | Self::Expired),
+            Self::PartiallyFilled => matches!(next, Self::Filled | Self::Cancelled | Self::Expired),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::New => "NEW",
+            Self::Acked => "ACKED",
+            Self::PartiallyFilled => "PARTIALLY_FILLED",
+            Self::Filled => "FILLED",
+            Self::Cancelled => "CANCELLED",
+            Self::Rejected => "REJECTED",
+            Self::Expired => "EXPIRED",
+        })
+    }
+}
+
+/// What an order is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRole {
+    Entry,
+    TakeProfit,
+    StopLoss,
+    /// One leg of a hedged (Up+Down) entry
+    Hedge,
+    /// Market-making bid
+    Quote,
+    /// SELL closing a recovered position
+    Flatten,
+    /// SELL of the unmatched shares of a hedged entry
+    Unwind,
+}
+
+impl fmt::Display for OrderRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Entry => "ENTRY",
+            Self::TakeProfit => "TP",
+            Self::StopLoss => "SL",
+            Self::Hedge => "HEDGE",
+            Self::Quote => "QUOTE",
+            Self::Flatten => "FLATTEN",
+            Self::Unwind => "UNWIND",
+        })
+    }
+}
+
+/// One status change
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub status: OrderStatus,
+    /// UNIX seconds
+    pub at: u64,
+    pub reason: String,
+}
+
+/// An order and everything that happened to it
+#[derive(Debug, Clone)]
+pub struct ManagedOrder {
+    pub key: OrderKey,
+    pub order_id: Option<String>,
+    pub asset: String,
+    pub role: OrderRole,
+    /// "UP" or "DOWN"
+    pub token: &'static str,
+    pub token_id: String,
+    /// "BUY" or "SELL"
+    pub side: &'static str,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub status: OrderStatus,
+    /// Fills are looked up from this time (UNIX seconds)
+    pub fills_since: u64,
+    pub history: Vec<Transition>,
+    pub fills: Vec<OrderFill>,
+    /// Filled shares already handed to the engine
+    pub reported: Decimal,
+}
+
+impl ManagedOrder {
+    pub fn filled(&self) -> Decimal {
+        self.fills.iter().map(|f| f.size).sum()
+    }
+
+    /// Volume-weighted average fill price
+    pub fn avg_price(&self) -> Option<Decimal> {
+        let filled = self.filled();
+        (!filled.is_zero()).then(|| self.fills.iter().map(|f| f.size * f.price).sum::<Decimal>() / filled)
+    }
+
+    /// Match time of the latest fill
+    pub fn last_fill_at(&self) -> Option<u64> {
+        self.fills.iter().map(|f| f.matched_at).max()
+    }
+
+    fn label(&self) -> String {
+        let id = self.order_id.as_deref().map(short_id).unwrap_or_else(|| format!("#{}", self.key));
+        format!("asset={} | {} {} {} | order_id={}", self.asset, self.role, self.side, self.token, id)
+    }
+}
+
+/// Every order placed in the current markets, by local key and venue order ID
+pub struct OrderManager {
+    tag: &'static str,
+    next_key: OrderKey,
+    orders: BTreeMap<OrderKey, ManagedOrder>,
+    by_id: HashMap<String, OrderKey>,
+    /// Trade + order ID of every recorded fill (later trade queries return the same trades again)
+    seen_fills: HashSet<(String, String)>,
+}
+
+impl OrderManager {
+    /// `tag` is the log tag ("LIVE")
+    pub fn new(tag: &'static str) -> Self {
+        Self {
+            tag,
+            next_key: 1,
+            orders: BTreeMap::new(),
+            by_id: HashMap::new(),
+            seen_fills: HashSet::new(),
+        }
+    }
+
+    /// Register an order about to be sent (New); fills are looked up from `fills_since`
+    pub fn submit(&mut self, role: OrderRole, side: &'static str, intent: &OrderIntent, now: u64, fills_since: u64) -> OrderKey {
+        let key = self.next_key;
+        self.next_key += 1;
+        let order = ManagedOrder {
+            key,
+            order_id: None,
+            asset: intent.asset.clone(),
+            role,
+            token: intent.token,
+            token_id: intent.token_id.clone(),
+            side,
+            price: intent.price,
+            size: intent.size,
+            status: OrderStatus::New,
+            fills_since,
+            history: vec![Transition { status: OrderStatus::New, at: now, reason: String::new() }],
+            fills: Vec::new(),
+            reported: Decimal::ZERO,
+        };
+        self.log(&format!("{} | NEW | price={:.2} | size={:.2}", order.label(), order.price, order.size));
+        self.orders.insert(key, order);
+        key
+    }
+
+    /// Take over an order placed before a restart; it starts out Acked
+    pub fn adopt(&mut self, role: OrderRole, side: &'static str, intent: &OrderIntent, order_id: &str, fills_since: u64, now: u64) -> OrderKey {
+        let key = self.submit(role, side, intent, now, fills_since);
+        self.acked(key, order_id, "recovered after restart", now);
+        key
+    }
+
+    /// The venue accepted the order under `order_id`
+    pub fn acked(&mut self, key: OrderKey, order_id: &str, reason: &str, now: u64) {
+        if let Some(order) = self.orders.get_mut(&key) {
+            order.order_id = Some(order_id.to_string());
+            self.by_id.insert(order_id.to_string(), key);
+        }
+        self.transition(key, OrderStatus::Acked, reason, now);
+    }
+
+    pub fn rejected(&mut self, key: OrderKey, reason: &str, now: u64) {
+        self.transition(key, OrderStatus::Rejected, reason, now);
+    }
+
+    pub fn cancelled(&mut self, key: OrderKey, reason: &str, now: u64) {
+        self.transition(key, OrderStatus::Cancelled, reason, now);
+    }
+
+    pub fn expired(&mut self, key: OrderKey, reason: &str, now: u64) {
+        self.transition(key, OrderStatus::Expired, reason, now);
+    }
+
+    /// Attach new fills to their orders (fills of orders we don't manage are ignored).
+    /// Fills that arrive after a cancel are still recorded; the status stays final.
+    pub fn record_fills(&mut self, fills: Vec<OrderFill>, now: u64) {
+        for fill in fills {
+            let Some(&key) = self.by_id.get(&fill.order_id) else {
+                continue;
+            };
+            if !self.seen_fills.insert((fill.trade_id.clone(), fill.order_id.clone())) {
+                continue;
+            }
+            let Some(order) = self.orders.get_mut(&key) else {
+                continue;
+            };
+            let msg = format!(
+                "{} | FILL | +{} @ {:.4} | matched_at={} | trade_id={}",
+                order.label(), fill.size, fill.price, fill.matched_at, short_id(&fill.trade_id)
+            );
+            order.fills.push(fill);
+            let (filled, size) = (order.filled(), order.size);
+            self.log(&format!("{} | filled={} of {:.2}", msg, filled, size));
+            if size - filled < FILLED_DUST {
+                self.transition(key, OrderStatus::Filled, "fully matched", now);
+            } else if self.orders.get(&key).is_some_and(|o| o.status == OrderStatus::Acked) {
+                self.transition(key, OrderStatus::PartiallyFilled, "partly matched", now);
+            }
+        }
+    }
+
+    /// Filled shares not yet handed to the engine, and their average price; marks them reported
+    pub fn take_unreported(&mut self, key: OrderKey) -> (Decimal, Option<Decimal>) {
+        let Some(order) = self.orders.get_mut(&key) else {
+            return (Decimal::ZERO, None);
+        };
+        let filled = order.filled();
+        let new = (filled - order.reported).max(Decimal::ZERO);
+        if new.is_zero() {
+            return (Decimal::ZERO, None);
+        }
+        // Average of the newest fills that make up the unreported shares
+        let mut left = new;
+        let mut notional = Decimal::ZERO;
+        for fill in order.fills.iter().rev() {
+            let take = fill.size.min(left);
+            notional += take * fill.price;
+            left -= take;
+            if left.is_zero() {
+                break;
+            }
+        }
+        order.reported = filled;
+        (new, Some(notional / new))
+    }
+
+    pub fn get(&self, key: OrderKey) -> Option<&ManagedOrder> {
+        self.orders.get(&key)
+    }
+
+    /// Whether `order_id` is one of our orders for `asset`
+    pub fn is_known(&self, asset: &str, order_id: &str) -> bool {
+        self.by_id.get(order_id).and_then(|k| self.orders.get(k)).is_some_and(|o| o.asset == asset)
+    }
+
+    /// Log the full history of every order of `asset` and drop them (its market ended)
+    pub fn close_market(&mut self, asset: &str) {
+        let keys: Vec<OrderKey> = self.orders.values().filter(|o| o.asset == asset).map(|o| o.key).collect();
+        for key in keys {
+            let Some(order) = self.orders.remove(&key) else {
+                continue;
+            };
+            if let Some(id) = &order.order_id {
+                self.by_id.remove(id);
+                self.seen_fills.retain(|(_, order_id)| order_id != id);
+            }
+            let history = order
+                .history
+                .iter()
+                .map(|t| format!("{}@{}", t.status, t.at))
+                .collect::<Vec<_>>()
+                .join(" → ");
+            self.log(&format!(
+                "{} | SUMMARY | status={} | filled={} of {:.2} | avg_price={} | {}",
+                order.label(), order.status, order.filled(), order.size,
+                order.avg_price().map(|p| format!("{:.4}", p)).unwrap_or_else(|| "-".to_string()), history
+            ));
+        }
+    }
+
+    fn transition(&mut self, key: OrderKey, status: OrderStatus, reason: &str, now: u64) {
+        let Some(order) = self.orders.get_mut(&key) else {
+            return;
+        };
+        if !order.status.can_move_to(status) {
+            return;
+        }
+        let from = order.status;
+        order.status = status;
+        order.history.push(Transition { status, at: now, reason: reason.to_string() });
+        let msg = format!("{} | {} → {} | {}", order.label(), from, status, reason);
+        self.log(&msg);
+    }
+
+    fn log(&self, msg: &str) {
+        let msg = format!("📋 [{}] ORDER | {}", self.tag, msg);
+        crate::log_event(&msg);
+        info!("{}", msg);
+    }
+}
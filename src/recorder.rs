@@ -0,0 +1,166 @@
+// Snapshot recorder - persists every MarketSnapshot as versioned JSONL for later replay
+//
+// One file per UTC day: `{dir}/snapshots-YYYY-MM-DD.jsonl`, one `SnapshotRecord` per line.
+
+use crate::models::MarketData;
+use crate::monitor::MarketSnapshot;
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Current record format version (bump when fields change incompatibly)
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// One recorded snapshot (a single JSONL line)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    /// Format version
+    pub v: u32,
+    /// Wall-clock capture time (UNIX milliseconds)
+    pub captured_at_ms: u64,
+    /// Start of the 15-minute period (UNIX seconds)
+    pub period_timestamp: u64,
+    pub time_remaining_seconds: u64,
+    /// Market data per asset symbol, with full bid/ask depth
+    pub markets: BTreeMap<String, MarketData>,
+    /// Known resolution per asset (1 = Up won, 0 = Down won); only set for converted backtest data
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outcomes: BTreeMap<String, u8>,
+}
+
+impl SnapshotRecord {
+    pub fn from_snapshot(snapshot: &MarketSnapshot) -> Self {
+        Self {
+            v: SNAPSHOT_FORMAT_VERSION,
+            captured_at_ms: snapshot.captured_at_ms,
+            period_timestamp: snapshot.period_timestamp,
+            time_remaining_seconds: snapshot.time_remaining_seconds,
+            markets: snapshot.markets.clone(),
+            outcomes: snapshot.outcomes.clone(),
+        }
+    }
+
+    /// Rebuild a snapshot for replay (`timestamp` is the replay time, not the capture time)
+    pub fn to_snapshot(&self) -> MarketSnapshot {
+        MarketSnapshot {
+            markets: self.markets.clone(),
+            timestamp: std::time::Instant::now(),
+            captured_at_ms: self.captured_at_ms,
+            time_remaining_seconds: self.time_remaining_seconds,
+            period_timestamp: self.period_timestamp,
+            outcomes: self.outcomes.clone(),
+        }
+    }
+}
+
+/// Appends snapshots to a per-day JSONL file, rotating at UTC midnight
+pub struct SnapshotRecorder {
+    directory: PathBuf,
+    /// Day (YYYY-MM-DD) and writer of the currently open file
+    current: Mutex<Option<(String, BufWriter<File>)>>,
+}
+
+impl SnapshotRecorder {
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)
+            .with_context(|| format!("Failed to create recording directory {}", directory.display()))?;
+        Ok(Self {
+            directory,
+            current: Mutex::new(None),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Path of the recording file for a given capture time
+    pub fn file_for(&self, captured_at_ms: u64) -> PathBuf {
+        self.directory.join(format!("snapshots-{}.jsonl", Self::day_of(captured_at_ms)))
+    }
+
+    fn day_of(captured_at_ms: u64) -> String {
+        Utc.timestamp_millis_opt(captured_at_ms as i64)
+            .single()
+            .unwrap_or_else(Utc::now)
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    /// Append one snapshot (flushed immediately so a crash loses at most one line)
+    pub fn record(&self, snapshot: &MarketSnapshot) -> Result<()> {
+        let line = serde_json::to_string(&SnapshotRecord::from_snapshot(snapshot))?;
+        let day = Self::day_of(snapshot.captured_at_ms);
+
+        let mut current = self
+            .current
+            .lock()
+            .map_err(|_| anyhow::anyhow!("recorder lock poisoned"))?;
+
+        if current.as_ref().map(|(open_day, _)| open_day != &day).unwrap_or(true) {
+            let path = self.file_for(snapshot.captured_at_ms);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open recording file {}", path.display()))?;
+            *current = Some((day, BufWriter::new(file)));
+        }
+
+        if let Some((_, writer)) = current.as_mut() {
+            writeln!(writer, "{}", line)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a recording file (or every `*.jsonl` file in a directory, in name order).
+/// Records from newer, unknown format versions are rejected.
+pub fn read_snapshots(path: &Path) -> Result<Vec<SnapshotRecord>> {
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .with_context(|| format!("Failed to read recording directory {}", path.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut records = Vec::new();
+    for file in files {
+        let reader = BufReader::new(
+            File::open(&file).with_context(|| format!("Failed to open recording {}", file.display()))?,
+        );
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: SnapshotRecord = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid snapshot record at {}:{}", file.display(), i + 1))?;
+            if record.v > SNAPSHOT_FORMAT_VERSION {
+                anyhow::bail!(
+                    "Snapshot format v{} at {}:{} is newer than supported v{}",
+                    record.v,
+                    file.display(),
+                    i + 1,
+                    SNAPSHOT_FORMAT_VERSION
+                );
+            }
+            records.push(record);
+        }
+    }
+
+    records.sort_by_key(|r| r.captured_at_ms);
+    Ok(records)
+}
@@ -0,0 +1,117 @@
+// Replay data source - drives the traders from recorded snapshots on a virtual clock
+
+use crate::monitor::{MarketDataSource, MarketSnapshot};
+use crate::recorder::{read_snapshots, SnapshotRecord};
+use anyhow::Result;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+/// How fast recorded time advances relative to wall-clock time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Recorded time × N (1x = real time)
+    Multiplier(f64),
+    /// No waiting between snapshots
+    Max,
+}
+
+impl std::str::FromStr for ReplaySpeed {
+    type Err = String;
+
+    /// Parse "max", "1x", "10x" or a bare multiplier like "2.5"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if s == "max" {
+            return Ok(ReplaySpeed::Max);
+        }
+        let factor: f64 = s
+            .trim_end_matches('x')
+            .parse()
+            .map_err(|_| format!("Invalid replay speed '{}' (use e.g. 1x, 10x or max)", s))?;
+        if factor <= 0.0 || !factor.is_finite() {
+            return Err(format!("Replay speed must be positive, got '{}'", s));
+        }
+        Ok(ReplaySpeed::Multiplier(factor))
+    }
+}
+
+/// Cursor and pacing anchor for a replay in progress
+struct ReplayState {
+    next: usize,
+    /// Wall-clock instant and recorded time (ms) of the first replayed snapshot
+    anchor: Option<(Instant, u64)>,
+}
+
+/// Serves recorded snapshots in capture order, paced by their recorded timestamps.
+///
+/// The virtual clock is the recorded `captured_at_ms` of each snapshot; pauses requested
+/// by the trader are already reflected in the recorded gaps, so they don't wait here.
+pub struct ReplaySource {
+    records: Arc<[SnapshotRecord]>,
+    speed: ReplaySpeed,
+    state: Mutex<ReplayState>,
+}
+
+impl ReplaySource {
+    /// Replay `records` (a `Vec`, or an `Arc` shared between backtests without copying)
+    pub fn new(records: impl Into<Arc<[SnapshotRecord]>>, speed: ReplaySpeed) -> Self {
+        Self {
+            records: records.into(),
+            speed,
+            state: Mutex::new(ReplayState { next: 0, anchor: None }),
+        }
+    }
+
+    /// Load a recording file or directory written by `SnapshotRecorder`
+    pub fn from_path(path: &Path, speed: ReplaySpeed) -> Result<Self> {
+        Ok(Self::new(read_snapshots(path)?, speed))
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Take the next record and the wall-clock instant it is due at
+    fn advance(&self) -> Result<Option<(SnapshotRecord, Option<Instant>)>> {
+        let mut state = self.state.lock().map_err(|_| anyhow::anyhow!("replay lock poisoned"))?;
+        let Some(record) = self.records.get(state.next).cloned() else {
+            return Ok(None);
+        };
+        state.next += 1;
+
+        let due = match self.speed {
+            ReplaySpeed::Max => None,
+            ReplaySpeed::Multiplier(factor) => {
+                let (start, first_ms) = *state.anchor.get_or_insert((Instant::now(), record.captured_at_ms));
+                let recorded_elapsed = record.captured_at_ms.saturating_sub(first_ms) as f64;
+                Some(start + Duration::from_secs_f64(recorded_elapsed / factor / 1000.0))
+            }
+        };
+        Ok(Some((record, due)))
+    }
+}
+
+impl MarketDataSource for ReplaySource {
+    async fn next_snapshot(&self) -> Result<Option<MarketSnapshot>> {
+        let Some((record, due)) = self.advance()? else {
+            return Ok(None);
+        };
+        if let Some(due) = due {
+            tokio::time::sleep_until(due).await;
+        }
+        Ok(Some(record.to_snapshot()))
+    }
+
+    async fn wait(&self, _poll_interval: Duration) {
+        // Pacing happens in next_snapshot against the recorded timestamps
+    }
+
+    async fn pause(&self, _duration: Duration) {
+        // Recorded gaps already include the live session's pauses
+    }
+}
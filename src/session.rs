@@ -0,0 +1,137 @@
+// Session journal - persists open positions and resting orders so a restarted live trader can resume them
+//
+// The whole session is one JSON file, rewritten (temp file + rename) whenever it changes.
+
+use crate::types::{ActiveCycle, PositionSide, PositionState};
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Current journal format version (bump when fields change incompatibly)
+pub const SESSION_FORMAT_VERSION: u32 = 2;
+
+/// Everything needed to take over the current market's positions after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Format version
+    pub v: u32,
+    /// Start of the 15-minute period the positions belong to (UNIX seconds)
+    pub period_timestamp: u64,
+    pub assets: BTreeMap<String, AssetSession>,
+}
+
+/// Open position, resting orders and market stats of one asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetSession {
+    pub condition_id: String,
+    pub up_token_id: Option<String>,
+    pub down_token_id: Option<String>,
+    pub current_cycle: Option<ActiveCycle>,
+    pub pending_entry: Option<JournaledEntry>,
+    pub hedge: PositionState,
+    pub total_pnl: Decimal,
+    pub wins: usize,
+    pub losses: usize,
+    pub total_fund_used: Decimal,
+    /// The executor's order bookkeeping
+    pub orders: JournaledOrders,
+}
+
+impl AssetSession {
+    /// Token ID of the Up or Down token
+    pub fn token_id(&self, side: PositionSide) -> Option<&str> {
+        match side {
+            PositionSide::LongUp => self.up_token_id.as_deref(),
+            PositionSide::LongDown => self.down_token_id.as_deref(),
+            PositionSide::Flat => None,
+        }
+    }
+}
+
+/// Entry order the engine is waiting on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournaledEntry {
+    pub side: PositionSide,
+    pub limit_price: Decimal,
+    /// Source-clock time (UNIX seconds) the order was placed
+    pub placed_at_secs: u64,
+}
+
+/// Orders an executor has resting for one asset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournaledOrders {
+    /// Entry BUY, confirmed by its fills
+    pub entry: Option<JournaledOrder>,
+    /// Resting TP SELL order ID
+    pub take_profit: Option<String>,
+}
+
+/// Resting BUY whose fills are read from the trades endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournaledOrder {
+    pub token_id: String,
+    pub order_id: Option<String>,
+    pub requested_size: Decimal,
+    /// Wall-clock time (UNIX seconds) the order was placed; fills are looked up from here
+    pub placed_at: u64,
+}
+
+/// Reads and writes the session journal file
+pub struct SessionJournal {
+    path: PathBuf,
+    /// Last JSON written, so unchanged state is not rewritten every tick
+    last_written: Option<String>,
+}
+
+impl SessionJournal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            last_written: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the journal, or None when there is no journal file yet
+    pub fn load(&self) -> Result<Option<SessionState>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read session journal {}", self.path.display()))?;
+        let state: SessionState = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid session journal {}", self.path.display()))?;
+        if state.v != SESSION_FORMAT_VERSION {
+            anyhow::bail!(
+                "Session journal {} has format version {} (expected {})",
+                self.path.display(), state.v, SESSION_FORMAT_VERSION
+            );
+        }
+        Ok(Some(state))
+    }
+
+    /// Write `state` if it changed since the last write. The file is replaced atomically,
+    /// so a crash mid-write leaves the previous journal intact.
+    pub fn save(&mut self, state: &SessionState) -> Result<()> {
+        let json = serde_json::to_string_pretty(state).context("Failed to serialize session journal")?;
+        if self.last_written.as_deref() == Some(json.as_str()) {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create journal directory {}", dir.display()))?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, &json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to replace session journal {}", self.path.display()))?;
+        self.last_written = Some(json);
+        Ok(())
+    }
+}
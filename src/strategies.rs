@@ -1,12 +1,15 @@
 // Strategy implementations
 
+use anyhow::Result;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
-use crate::types::PricePoint;
+use crate::models::MarketData;
+use crate::types::{PositionSide, PositionState, PricePoint, MARKET_DURATION_SECS};
 use crate::config::{StrategyConfig, IndexType};
-use crate::indicators::{RollingRSI, RollingMACD, RollingMomentum, calculate_rsi};
+use crate::indicators::{Indicator, MarketIndicators, RealizedVolatility, Sma, TokenIndicators};
 
 /// Trading action decision from strategy
 #[derive(Debug, Clone)]
@@ -19,226 +22,763 @@ pub enum TradeAction {
         price: Decimal,
         shares: Decimal,
     },
+    /// Buy Up and Down in matched size and hold both to resolution
+    BuyBoth {
+        up_price: Decimal,
+        down_price: Decimal,
+        shares: Decimal,
+        /// Fee rate on each leg's notional (for the locked-in profit)
+        fee_rate: Decimal,
+    },
     SellUp {
         price: Decimal,
     },
     SellDown {
         price: Decimal,
     },
+    /// An entry signal the strategy's own filter held back (logged while flat)
+    Hold {
+        reason: String,
+    },
     NoAction,
 }
 
+/// A resting BUY limit a market-making strategy wants on one token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// Bids to keep on the Up and Down tokens (`None` = no quote on that side)
+#[derive(Debug, Clone, Default)]
+pub struct Quotes {
+    pub up: Option<Quote>,
+    pub down: Option<Quote>,
+}
+
 /// Strategy trait for trading decisions
 pub trait Strategy: Send + Sync {
     fn name(&self) -> &str;
     fn config(&self) -> &StrategyConfig;
-    fn calculate_index(
-        &self,
-        prices: &[PricePoint],
-        rsi_calc: &RollingRSI,
-        macd_calc: &RollingMACD,
-        momentum_calc: &RollingMomentum,
-    ) -> Option<f64>;
-    fn decide(
-        &self,
-        prices: &[PricePoint],
-        rsi_calc: &RollingRSI,
-        macd_calc: &RollingMACD,
-        momentum_calc: &RollingMomentum,
-    ) -> TradeAction;
+    /// Trending index of one token from its rolling indicators
+    fn calculate_index(&self, indicators: &TokenIndicators) -> Option<f64>;
+    /// Decision for the latest price point given both tokens' indicator state
+    fn decide(&self, point: &PricePoint, indicators: &MarketIndicators) -> TradeAction;
+
+    /// Indicators this strategy needs besides the configured index (built per token, in `TokenIndicators::extra`)
+    fn extra_indicators(&self) -> Vec<Box<dyn Indicator>> {
+        Vec::new()
+    }
+
+    /// Fresh indicator state for one market
+    fn new_indicators(&self) -> MarketIndicators {
+        let mut indicators = MarketIndicators::new(self.config());
+        indicators.up.extra = self.extra_indicators();
+        indicators.down.extra = self.extra_indicators();
+        indicators
+    }
+
+    /// Reason to keep an open cycle through a stop-loss hit (`None` = take the SL)
+    fn hold_through_stop_loss(&self, _side: PositionSide, _indicators: &MarketIndicators) -> Option<String> {
+        None
+    }
+
+    /// TP and SL offsets from the entry price for a new cycle
+    fn exit_offsets(&self, _side: PositionSide, _entry_price: Decimal) -> (Decimal, Decimal) {
+        (self.config().profit_threshold, self.config().sl_threshold)
+    }
+
+    /// Bids to rest on both tokens given the books, the inventory held and the seconds left in the market.
+    /// `None` for taker strategies, which trade through `decide` instead.
+    fn quotes(&self, _market: &MarketData, _inventory: &PositionState, _time_remaining_secs: u64) -> Option<Quotes> {
+        None
+    }
+
+    /// Whether the strategy prices off the bids too, so ask-only price data can't drive it
+    fn needs_bids(&self) -> bool {
+        false
+    }
 }
 
 /// Momentum Hedge Strategy implementation
 pub struct MomentumHedgeStrategy {
     config: StrategyConfig,
+    /// Strategy name with the index it trades on
+    name: String,
 }
 
 impl MomentumHedgeStrategy {
     pub fn new(config: StrategyConfig) -> Self {
-        Self { config }
+        let name = format!("MomentumHedgeStrategy ({})", config.index_type.spec().name);
+        Self { config, name }
+    }
+
+    /// Index as `calculate_index` returned it for the previous price
+    fn previous_index(&self, token: &TokenIndicators) -> Option<f64> {
+        if token.samples() > self.config.lookback {
+            token.previous_value()
+        } else {
+            None
+        }
+    }
+
+    /// The token's index (or composite entry condition) says it is trending
+    fn trending(&self, token: &TokenIndicators) -> bool {
+        // A composite condition replaces the single threshold check
+        if let Some(condition) = &self.config.entry_condition {
+            return condition.evaluate(&token.conditions);
+        }
+        match self.calculate_index(token) {
+            Some(index) if self.config.index_type == IndexType::Momentum => index > self.config.momentum_threshold_pct,
+            Some(index) => index > self.config.trend_threshold,
+            None => false,
+        }
+    }
+
+    /// MACD mode only enters while MACD is increasing (momentum acceleration); the reason to hold otherwise
+    fn not_accelerating(&self, token: &TokenIndicators, label: &str) -> Option<String> {
+        if self.config.index_type != IndexType::MACD {
+            return None;
+        }
+        let (current, previous) = (self.calculate_index(token), self.previous_index(token));
+        let increasing = match (current, previous) {
+            (Some(c), Some(p)) => c > p,
+            (Some(_), None) => true, // First MACD value, no previous to compare
+            _ => false,
+        };
+        (!increasing).then(|| format!(
+            "MACD NOT INCREASING | MACD_{}={:.4} | previous={:.4} | momentum not accelerating",
+            label,
+            current.unwrap_or(0.0),
+            previous.unwrap_or(0.0)
+        ))
+    }
+
+    /// MACD crossed above its signal line on the latest price
+    fn crosses_above(&self, token: &TokenIndicators) -> bool {
+        let macd = self.calculate_index(token);
+        let signal = token.index.signal();
+        match (macd, signal, self.previous_index(token), token.previous_signal()) {
+            (Some(m), Some(s), Some(pm), Some(ps)) => pm <= ps && m > s,
+            // First values: if MACD > Signal, consider it a crossover
+            (Some(m), Some(s), None, None) => m > s,
+            _ => false,
+        }
     }
 }
 
 impl Strategy for MomentumHedgeStrategy {
     fn name(&self) -> &str {
-        // Return a dynamic name based on the index type being used
-        match self.config.index_type {
-            IndexType::RSI => "MomentumHedgeStrategy (RSI)",
-            IndexType::MACD => "MomentumHedgeStrategy (MACD)",
-            IndexType::MACDSignal => "MomentumHedgeStrategy (MACD Signal)",
-            IndexType::Momentum => "MomentumHedgeStrategy (Momentum)",
-        }
+        &self.name
     }
 
     fn config(&self) -> &StrategyConfig {
         &self.config
     }
 
-    fn calculate_index(
-        &self,
-        prices: &[PricePoint],
-        rsi_calc: &RollingRSI,
-        macd_calc: &RollingMACD,
-        momentum_calc: &RollingMomentum,
-    ) -> Option<f64> {
-        if prices.len() < self.config.lookback {
+    fn calculate_index(&self, indicators: &TokenIndicators) -> Option<f64> {
+        if indicators.samples() < self.config.lookback {
             return None;
         }
 
-        match self.config.index_type {
-            IndexType::RSI => {
-                if rsi_calc.is_ready() {
-                    rsi_calc.get_rsi()
-                } else {
-                    let price_slice: Vec<f64> = prices
-                        .iter()
-                        .rev()
-                        .take(self.config.lookback + 1)
-                        .map(|p| p.up_price)
-                        .collect();
-                    if price_slice.len() >= self.config.lookback + 1 {
-                        calculate_rsi(&price_slice, self.config.lookback)
-                    } else {
-                        None
-                    }
-                }
-            }
-            IndexType::MACD => {
-                if macd_calc.is_ready() {
-                    macd_calc.get_macd()
-                } else {
-                    None
-                }
-            }
-            IndexType::MACDSignal => {
-                // For signal line mode, return MACD value (signal line checked separately)
-                if macd_calc.is_ready() {
-                    macd_calc.get_macd()
-                } else {
-                    None
-                }
-            }
-            IndexType::Momentum => {
-                if momentum_calc.is_ready() {
-                    momentum_calc.get_momentum()
-                } else {
-                    None
-                }
-            }
+        // For signal line mode this is the MACD value (signal line checked separately)
+        if indicators.index.is_ready() {
+            indicators.index.value()
+        } else {
+            None
         }
     }
 
-    fn decide(
-        &self,
-        prices: &[PricePoint],
-        rsi_calc: &RollingRSI,
-        macd_calc: &RollingMACD,
-        momentum_calc: &RollingMomentum,
-    ) -> TradeAction {
-        if prices.is_empty() || prices.len() < self.config.lookback {
+    fn decide(&self, point: &PricePoint, indicators: &MarketIndicators) -> TradeAction {
+        let buy_up = || TradeAction::BuyUp {
+            price: Decimal::try_from(point.up_price).unwrap_or(dec!(0.0)),
+            shares: self.config.position_size_shares,
+        };
+        let buy_down = || TradeAction::BuyDown {
+            price: Decimal::try_from(point.down_price).unwrap_or(dec!(0.0)),
+            shares: self.config.position_size_shares,
+        };
+
+        // MACDSignal mode: enter on MACD crossing above its signal line
+        if self.config.index_type == IndexType::MACDSignal {
+            if self.crosses_above(&indicators.up) {
+                return buy_up();
+            } else if self.crosses_above(&indicators.down) {
+                return buy_down();
+            }
             return TradeAction::NoAction;
         }
 
-        // Calculate trending index for Up token
-        let up_index = self.calculate_index(prices, rsi_calc, macd_calc, momentum_calc);
-        
-        // Calculate trending index for Down token
-        let down_prices: Vec<f64> = prices.iter().map(|p| p.down_price).collect();
-        let mut temp_rsi_calc_down = RollingRSI::new(self.config.lookback);
-        let mut temp_macd_calc_down = RollingMACD::new(self.config.macd_fast_period, self.config.macd_slow_period);
-        let mut temp_momentum_calc_down = RollingMomentum::new(self.config.lookback);
-        
-        for &down_price in &down_prices {
-            temp_rsi_calc_down.add_price(down_price);
-            temp_macd_calc_down.add_price(down_price);
-            temp_momentum_calc_down.add_price(down_price);
-        }
-        
-        let down_index = match self.config.index_type {
-            IndexType::RSI => {
-                if temp_rsi_calc_down.is_ready() {
-                    temp_rsi_calc_down.get_rsi()
-                } else {
-                    let price_slice: Vec<f64> = prices
-                        .iter()
-                        .rev()
-                        .take(self.config.lookback + 1)
-                        .map(|p| p.down_price)
-                        .collect();
-                    if price_slice.len() >= self.config.lookback + 1 {
-                        calculate_rsi(&price_slice, self.config.lookback)
-                    } else {
-                        None
-                    }
-                }
-            }
-            IndexType::MACD => {
-                if temp_macd_calc_down.is_ready() {
-                    temp_macd_calc_down.get_macd()
-                } else {
-                    None
-                }
-            }
-            IndexType::MACDSignal => {
-                if temp_macd_calc_down.is_ready() {
-                    temp_macd_calc_down.get_macd()
-                } else {
-                    None
-                }
+        // Trade the token that meets the condition
+        if self.trending(&indicators.up) {
+            if let Some(reason) = self.not_accelerating(&indicators.up, "up") {
+                return TradeAction::Hold { reason };
             }
-            IndexType::Momentum => {
-                if temp_momentum_calc_down.is_ready() {
-                    temp_momentum_calc_down.get_momentum()
-                } else {
-                    None
-                }
+            return buy_up();
+        } else if self.trending(&indicators.down) {
+            if let Some(reason) = self.not_accelerating(&indicators.down, "down") {
+                return TradeAction::Hold { reason };
             }
+            return buy_down();
+        }
+
+        TradeAction::NoAction
+    }
+
+    fn hold_through_stop_loss(&self, side: PositionSide, indicators: &MarketIndicators) -> Option<String> {
+        // MACD SL filter: hold through the SL while the held token's MACD is still positive
+        if self.config.index_type != IndexType::MACD || !self.config.use_macd_sl_filter {
+            return None;
+        }
+        let held = match side {
+            PositionSide::LongUp => &indicators.up,
+            PositionSide::LongDown => &indicators.down,
+            PositionSide::Flat => return None,
         };
+        match self.calculate_index(held) {
+            Some(macd) if macd > 0.0 => Some(format!(
+                "MACD={:.4} > 0 | price condition met but momentum still positive",
+                macd
+            )),
+            _ => None,
+        }
+    }
+}
 
-        // Determine which token meets the condition
-        // Note: For MACDSignal mode, crossover detection is handled in simulation.rs and trading.rs
-        // This method will not be used for entry decisions in MACDSignal mode
-        let up_trending = match up_index {
-            Some(index) => {
-                match self.config.index_type {
-                    IndexType::RSI => index > self.config.trend_threshold,
-                    IndexType::MACD => index > self.config.trend_threshold,
-                    IndexType::MACDSignal => false, // Crossover handled elsewhere
-                    IndexType::Momentum => index > self.config.momentum_threshold_pct,
-                }
-            }
-            None => false,
+/// Settings for `MeanReversionStrategy` (config.json `strategies.mean_reversion`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MeanReversionConfig {
+    /// RSI of the expensive side that counts as overbought
+    pub overbought: f64,
+    /// How far the expensive side must trade above its moving average
+    pub overshoot: f64,
+    /// Moving average window (prices)
+    pub mean_period: usize,
+    /// Only buy the cheap side at or below this price
+    pub max_entry_price: f64,
+    /// ...and above this one (a near-zero token has usually already lost)
+    pub min_entry_price: f64,
+    /// TP = entry + this fraction of the distance back to 0.50 (at least `profit_threshold`)
+    pub tp_reversion: f64,
+    /// SL offset below entry (fades need more room than trend entries)
+    pub stop_loss: f64,
+}
+
+impl Default for MeanReversionConfig {
+    fn default() -> Self {
+        Self {
+            overbought: 80.0,
+            overshoot: 0.05,
+            mean_period: 20,
+            max_entry_price: 0.45,
+            min_entry_price: 0.10,
+            tp_reversion: 0.5,
+            stop_loss: 0.10,
+        }
+    }
+}
+
+/// Mean reversion: when one side's RSI is overbought and its price overshoots its
+/// moving average, fade the move by buying the cheap (opposite) side.
+pub struct MeanReversionStrategy {
+    config: StrategyConfig,
+    settings: MeanReversionConfig,
+}
+
+impl MeanReversionStrategy {
+    /// `config.index_type` must be RSI: the trend index is the RSI used for the overbought check
+    pub fn new(config: StrategyConfig, settings: MeanReversionConfig) -> Self {
+        Self { config, settings }
+    }
+
+    /// The expensive side is overbought and stretched above its mean
+    fn overextended(&self, price: f64, rsi: Option<f64>, token: &TokenIndicators) -> bool {
+        let mean = token.extra.first().and_then(|sma| sma.value());
+        match (rsi, mean) {
+            (Some(rsi), Some(mean)) => rsi > self.settings.overbought && price >= mean + self.settings.overshoot,
+            _ => false,
+        }
+    }
+}
+
+impl Strategy for MeanReversionStrategy {
+    fn name(&self) -> &str {
+        "MeanReversionStrategy (RSI)"
+    }
+
+    fn config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn calculate_index(&self, indicators: &TokenIndicators) -> Option<f64> {
+        if indicators.samples() < self.config.lookback || !indicators.index.is_ready() {
+            return None;
+        }
+        indicators.index.value()
+    }
+
+    fn decide(&self, point: &PricePoint, indicators: &MarketIndicators) -> TradeAction {
+        let up_rsi = self.calculate_index(&indicators.up);
+        let down_rsi = self.calculate_index(&indicators.down);
+        let cheap_enough = |price: f64| price >= self.settings.min_entry_price && price <= self.settings.max_entry_price;
+
+        // Up overextended → buy Down (and vice versa)
+        if self.overextended(point.up_price, up_rsi, &indicators.up) && cheap_enough(point.down_price) {
+            return TradeAction::BuyDown {
+                price: Decimal::try_from(point.down_price).unwrap_or(dec!(0.0)),
+                shares: self.config.position_size_shares,
+            };
+        }
+        if self.overextended(point.down_price, down_rsi, &indicators.down) && cheap_enough(point.up_price) {
+            return TradeAction::BuyUp {
+                price: Decimal::try_from(point.up_price).unwrap_or(dec!(0.0)),
+                shares: self.config.position_size_shares,
+            };
+        }
+        TradeAction::NoAction
+    }
+
+    fn extra_indicators(&self) -> Vec<Box<dyn Indicator>> {
+        vec![Box::new(Sma::new(self.settings.mean_period))]
+    }
+
+    fn exit_offsets(&self, _side: PositionSide, entry_price: Decimal) -> (Decimal, Decimal) {
+        let reversion = (dec!(0.5) - entry_price) * Decimal::try_from(self.settings.tp_reversion).unwrap_or(dec!(0.5));
+        let tp = reversion.max(self.config.profit_threshold).round_dp(2);
+        let sl = Decimal::try_from(self.settings.stop_loss).unwrap_or(self.config.sl_threshold);
+        (tp, sl)
+    }
+}
+
+/// Settings for `SumArbitrageStrategy` (config.json `strategies.sum_arbitrage`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SumArbitrageConfig {
+    /// Taker fee as a fraction of the notional paid for each leg
+    pub fee_rate: f64,
+    /// Minimum profit per matched Up+Down pair after fees
+    pub min_edge: f64,
+}
+
+impl Default for SumArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            fee_rate: 0.01,
+            min_edge: 0.005,
+        }
+    }
+}
+
+/// Sum arbitrage: Up and Down together pay exactly 1.0, so when both asks plus fees
+/// cost less than that, buy both sides in matched size and hold them to resolution.
+pub struct SumArbitrageStrategy {
+    config: StrategyConfig,
+    settings: SumArbitrageConfig,
+}
+
+impl SumArbitrageStrategy {
+    pub fn new(config: StrategyConfig, settings: SumArbitrageConfig) -> Self {
+        Self { config, settings }
+    }
+
+    /// Profit per matched pair bought at these asks, after fees
+    fn edge(&self, up_ask: f64, down_ask: f64) -> f64 {
+        1.0 - (up_ask + down_ask) * (1.0 + self.settings.fee_rate)
+    }
+}
+
+impl Strategy for SumArbitrageStrategy {
+    fn name(&self) -> &str {
+        "SumArbitrageStrategy"
+    }
+
+    fn config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn calculate_index(&self, indicators: &TokenIndicators) -> Option<f64> {
+        // Only logged; entries depend on prices alone
+        if indicators.samples() < self.config.lookback || !indicators.index.is_ready() {
+            return None;
+        }
+        indicators.index.value()
+    }
+
+    fn decide(&self, point: &PricePoint, _indicators: &MarketIndicators) -> TradeAction {
+        if point.up_price <= 0.0 || point.down_price <= 0.0 {
+            return TradeAction::NoAction;
+        }
+        if self.edge(point.up_price, point.down_price) < self.settings.min_edge {
+            return TradeAction::NoAction;
+        }
+        TradeAction::BuyBoth {
+            up_price: Decimal::try_from(point.up_price).unwrap_or(dec!(0.0)),
+            down_price: Decimal::try_from(point.down_price).unwrap_or(dec!(0.0)),
+            shares: self.config.position_size_shares,
+            fee_rate: Decimal::try_from(self.settings.fee_rate).unwrap_or(dec!(0.0)),
+        }
+    }
+}
+
+/// Settings for `MarketMakingStrategy` (config.json `strategies.market_making`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MarketMakingConfig {
+    /// Distance of each bid below fair value at the start of the market
+    pub half_spread: f64,
+    /// Extra half spread at expiry, as a multiple of `half_spread` (grows linearly with elapsed time)
+    pub expiry_widening: f64,
+    /// Fair value shift per share of net inventory (long Up lowers the Up bid and raises the Down bid)
+    pub inventory_skew: f64,
+    /// Stop bidding for the side that is already this many shares net long
+    pub max_inventory: f64,
+    /// Pull all quotes this many seconds before the market ends
+    pub pull_before_secs: u64,
+}
+
+impl Default for MarketMakingConfig {
+    fn default() -> Self {
+        Self {
+            half_spread: 0.02,
+            expiry_widening: 1.0,
+            inventory_skew: 0.001,
+            max_inventory: 50.0,
+            pull_before_secs: 60,
+        }
+    }
+}
+
+/// Market making: rest bids on Up and Down around a fair value taken from both books,
+/// skewed by inventory and widened as expiry approaches. A filled Up bid and Down bid
+/// form a pair that pays 1.0, so inventory is held to resolution.
+pub struct MarketMakingStrategy {
+    config: StrategyConfig,
+    settings: MarketMakingConfig,
+}
+
+impl MarketMakingStrategy {
+    pub fn new(config: StrategyConfig, settings: MarketMakingConfig) -> Self {
+        Self { config, settings }
+    }
+
+    /// Bid at `fair - half_spread`, rounded down to the cent and kept below the best ask (so it rests on the book)
+    fn bid(fair: Decimal, half_spread: Decimal, best_ask: Option<Decimal>, size: Decimal) -> Option<Quote> {
+        let mut price = (fair - half_spread).round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero);
+        if let Some(ask) = best_ask {
+            price = price.min(ask - dec!(0.01));
+        }
+        (price >= dec!(0.01) && price <= dec!(0.99) && size > Decimal::ZERO).then_some(Quote { price, size })
+    }
+}
+
+impl Strategy for MarketMakingStrategy {
+    fn name(&self) -> &str {
+        "MarketMakingStrategy"
+    }
+
+    fn config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn calculate_index(&self, indicators: &TokenIndicators) -> Option<f64> {
+        // Only logged; quotes depend on the books alone
+        if indicators.samples() < self.config.lookback || !indicators.index.is_ready() {
+            return None;
+        }
+        indicators.index.value()
+    }
+
+    fn decide(&self, _point: &PricePoint, _indicators: &MarketIndicators) -> TradeAction {
+        TradeAction::NoAction
+    }
+
+    fn quotes(&self, market: &MarketData, inventory: &PositionState, time_remaining_secs: u64) -> Option<Quotes> {
+        // Too close to rollover: pull everything
+        if time_remaining_secs <= self.settings.pull_before_secs {
+            return Some(Quotes::default());
+        }
+        let (Some(up), Some(down)) = (market.up_token.as_ref(), market.down_token.as_ref()) else {
+            return Some(Quotes::default());
+        };
+        // Fair Up from its own mid and the Down book (fair_up ≈ 1 - mid_down)
+        let fair_up = match (up.mid(), down.mid()) {
+            (Some(up_mid), Some(down_mid)) => (up_mid + Decimal::ONE - down_mid) / Decimal::TWO,
+            (Some(up_mid), None) => up_mid,
+            (None, Some(down_mid)) => Decimal::ONE - down_mid,
+            (None, None) => return Some(Quotes::default()),
         };
 
-        let down_trending = match down_index {
-            Some(index) => {
-                match self.config.index_type {
-                    IndexType::RSI => index > self.config.trend_threshold,
-                    IndexType::MACD => index > self.config.trend_threshold,
-                    IndexType::MACDSignal => false, // Crossover handled elsewhere
-                    IndexType::Momentum => index > self.config.momentum_threshold_pct,
-                }
+        let decimal = |v: f64| Decimal::try_from(v).unwrap_or(Decimal::ZERO);
+        // Share of the market gone, from the snapshot's time remaining
+        let duration = Decimal::from(MARKET_DURATION_SECS);
+        let elapsed = (duration - Decimal::from(time_remaining_secs.min(MARKET_DURATION_SECS))) / duration;
+        let half_spread = decimal(self.settings.half_spread) * (Decimal::ONE + decimal(self.settings.expiry_widening) * elapsed);
+
+        // Net long Up lowers fair Up (and raises fair Down) so the next fills flatten the inventory
+        let net_up = inventory.net_up_shares();
+        let skew = net_up * decimal(self.settings.inventory_skew);
+        let fair_up = fair_up - skew;
+        let fair_down = Decimal::ONE - fair_up;
+
+        let max_inventory = decimal(self.settings.max_inventory);
+        let size = self.config.position_size_shares;
+        Some(Quotes {
+            up: if net_up < max_inventory { Self::bid(fair_up, half_spread, up.ask, size) } else { None },
+            down: if -net_up < max_inventory { Self::bid(fair_down, half_spread, down.ask, size) } else { None },
+        })
+    }
+
+    fn needs_bids(&self) -> bool {
+        true
+    }
+}
+
+/// Settings for `ExpiryConvergenceStrategy` (config.json `strategies.expiry_convergence`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExpiryConvergenceConfig {
+    /// Only enter in the last this many minutes of the market
+    pub window_minutes: u64,
+    /// Buy the side priced at or above this level
+    pub confidence: f64,
+    /// ...and at or below this one (little upside left above it)
+    pub max_entry_price: f64,
+    /// Window (prices) for the stability check
+    pub stable_period: usize,
+    /// The side's average over `stable_period` must be at least `confidence` minus this
+    pub max_pullback: f64,
+    /// Max realized volatility (% std of tick log returns) over `stable_period`
+    pub max_volatility: f64,
+    /// Size at expiry as a fraction of `position_size` (full size at the start of the window)
+    pub min_size_fraction: f64,
+    /// SL offset below entry; positions are otherwise held to resolution
+    pub stop_loss: f64,
+}
+
+impl Default for ExpiryConvergenceConfig {
+    fn default() -> Self {
+        Self {
+            window_minutes: 3,
+            confidence: 0.85,
+            max_entry_price: 0.97,
+            stable_period: 10,
+            max_pullback: 0.02,
+            max_volatility: 2.0,
+            min_size_fraction: 0.25,
+            stop_loss: 0.15,
+        }
+    }
+}
+
+/// Expiry convergence: late in the market the favourite tends to converge to 1.0.
+/// In the last `window_minutes`, buy the side priced at or above `confidence` while its
+/// recent prices are steady, sizing down as expiry approaches, and hold it to resolution.
+pub struct ExpiryConvergenceStrategy {
+    config: StrategyConfig,
+    settings: ExpiryConvergenceConfig,
+}
+
+impl ExpiryConvergenceStrategy {
+    pub fn new(config: StrategyConfig, settings: ExpiryConvergenceConfig) -> Self {
+        Self { config, settings }
+    }
+
+    /// Price in the entry band, and the recent average and volatility show a steady trend
+    fn converging(&self, price: f64, token: &TokenIndicators) -> bool {
+        if price < self.settings.confidence || price > self.settings.max_entry_price {
+            return false;
+        }
+        let mean = token.extra.first().and_then(|sma| sma.value());
+        let volatility = token.extra.get(1).and_then(|vol| vol.value());
+        match (mean, volatility) {
+            (Some(mean), Some(volatility)) => {
+                mean >= self.settings.confidence - self.settings.max_pullback && volatility <= self.settings.max_volatility
             }
-            None => false,
+            _ => false,
+        }
+    }
+
+    /// Full size at the start of the window, shrinking linearly to `min_size_fraction` at expiry
+    fn shares(&self, time_remaining_secs: u64) -> Decimal {
+        let window_secs = (self.settings.window_minutes * 60).max(1) as f64;
+        let left = (time_remaining_secs as f64 / window_secs).clamp(0.0, 1.0);
+        let min = self.settings.min_size_fraction.clamp(0.0, 1.0);
+        let fraction = Decimal::try_from(min + (1.0 - min) * left).unwrap_or(Decimal::ONE);
+        (self.config.position_size_shares * fraction).round_dp(2)
+    }
+}
+
+impl Strategy for ExpiryConvergenceStrategy {
+    fn name(&self) -> &str {
+        "ExpiryConvergenceStrategy"
+    }
+
+    fn config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn calculate_index(&self, indicators: &TokenIndicators) -> Option<f64> {
+        // Only logged; entries depend on price, stability and time left
+        if indicators.samples() < self.config.lookback || !indicators.index.is_ready() {
+            return None;
+        }
+        indicators.index.value()
+    }
+
+    fn decide(&self, point: &PricePoint, indicators: &MarketIndicators) -> TradeAction {
+        let Some(remaining) = point.time_remaining_secs else {
+            return TradeAction::NoAction;
         };
+        if remaining > self.settings.window_minutes * 60 {
+            return TradeAction::NoAction;
+        }
+        let shares = self.shares(remaining);
+        if shares <= Decimal::ZERO {
+            return TradeAction::NoAction;
+        }
 
-        // Trade the token that meets the condition
-        if up_trending {
-            let current_price = Decimal::try_from(prices.last().unwrap().up_price)
-                .unwrap_or(dec!(0.0));
+        if self.converging(point.up_price, &indicators.up) {
             return TradeAction::BuyUp {
-                price: current_price,
-                shares: self.config.position_size_shares,
+                price: Decimal::try_from(point.up_price).unwrap_or(dec!(0.0)),
+                shares,
             };
-        } else if down_trending {
-            let current_price = Decimal::try_from(prices.last().unwrap().down_price)
-                .unwrap_or(dec!(0.0));
+        }
+        if self.converging(point.down_price, &indicators.down) {
             return TradeAction::BuyDown {
-                price: current_price,
-                shares: self.config.position_size_shares,
+                price: Decimal::try_from(point.down_price).unwrap_or(dec!(0.0)),
+                shares,
             };
         }
-
         TradeAction::NoAction
     }
+
+    fn extra_indicators(&self) -> Vec<Box<dyn Indicator>> {
+        vec![
+            Box::new(Sma::new(self.settings.stable_period)),
+            Box::new(RealizedVolatility::new(self.settings.stable_period)),
+        ]
+    }
+
+    fn exit_offsets(&self, _side: PositionSide, _entry_price: Decimal) -> (Decimal, Decimal) {
+        // TP above 1.0 means no TP order: the position rides to resolution unless the SL hits
+        let sl = Decimal::try_from(self.settings.stop_loss).unwrap_or(self.config.sl_threshold);
+        (Decimal::ONE, sl)
+    }
+}
+
+/// Builds a strategy from the shared `StrategyConfig` and its own `strategies.<name>` section of config.json
+pub type StrategyFactory = fn(StrategyConfig, Option<&serde_json::Value>) -> Result<Box<dyn Strategy>>;
+
+fn momentum_hedge(config: StrategyConfig, _section: Option<&serde_json::Value>) -> Result<Box<dyn Strategy>> {
+    Ok(Box::new(MomentumHedgeStrategy::new(config)))
+}
+
+fn mean_reversion(config: StrategyConfig, section: Option<&serde_json::Value>) -> Result<Box<dyn Strategy>> {
+    let settings: MeanReversionConfig = match section {
+        Some(section) => serde_json::from_value(section.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid strategies.mean_reversion config: {}", e))?,
+        None => MeanReversionConfig::default(),
+    };
+    // lookback and thresholds tuned for another index would silently drive the RSI check
+    if config.index_type != IndexType::RSI {
+        anyhow::bail!(
+            "mean_reversion needs the RSI index, but trending_index.mode selects {}",
+            config.index_type.label()
+        );
+    }
+    if settings.mean_period == 0 {
+        anyhow::bail!("strategies.mean_reversion.mean_period must be at least 1");
+    }
+    Ok(Box::new(MeanReversionStrategy::new(config, settings)))
+}
+
+fn sum_arbitrage(config: StrategyConfig, section: Option<&serde_json::Value>) -> Result<Box<dyn Strategy>> {
+    let settings: SumArbitrageConfig = match section {
+        Some(section) => serde_json::from_value(section.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid strategies.sum_arbitrage config: {}", e))?,
+        None => SumArbitrageConfig::default(),
+    };
+    Ok(Box::new(SumArbitrageStrategy::new(config, settings)))
+}
+
+fn market_making(config: StrategyConfig, section: Option<&serde_json::Value>) -> Result<Box<dyn Strategy>> {
+    let settings: MarketMakingConfig = match section {
+        Some(section) => serde_json::from_value(section.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid strategies.market_making config: {}", e))?,
+        None => MarketMakingConfig::default(),
+    };
+    Ok(Box::new(MarketMakingStrategy::new(config, settings)))
+}
+
+fn expiry_convergence(config: StrategyConfig, section: Option<&serde_json::Value>) -> Result<Box<dyn Strategy>> {
+    let settings: ExpiryConvergenceConfig = match section {
+        Some(section) => serde_json::from_value(section.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid strategies.expiry_convergence config: {}", e))?,
+        None => ExpiryConvergenceConfig::default(),
+    };
+    if settings.stable_period == 0 {
+        anyhow::bail!("strategies.expiry_convergence.stable_period must be at least 1");
+    }
+    Ok(Box::new(ExpiryConvergenceStrategy::new(config, settings)))
+}
+
+/// Strategy implementations selectable by `--strategy` name
+#[derive(Clone, Default)]
+pub struct StrategyRegistry {
+    factories: BTreeMap<String, StrategyFactory>,
+}
+
+impl StrategyRegistry {
+    /// Registry with every strategy that ships with the bot.
+    /// Index mode names (`rsi`, `macd`, ...) select `MomentumHedgeStrategy` on that index.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register("momentum_hedge", momentum_hedge);
+        registry.register("mean_reversion", mean_reversion);
+        registry.register("sum_arbitrage", sum_arbitrage);
+        registry.register("market_making", market_making);
+        registry.register("expiry_convergence", expiry_convergence);
+        for mode in IndexType::modes() {
+            registry.register(mode, momentum_hedge);
+        }
+        registry
+    }
+
+    /// Add (or replace) a strategy under `name`
+    pub fn register(&mut self, name: &str, factory: StrategyFactory) {
+        self.factories.insert(name.to_lowercase(), factory);
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(|k| k.as_str())
+    }
+
+    /// Look up `name` and pair it with its config section
+    pub fn spec(&self, name: &str, section: Option<serde_json::Value>) -> Result<StrategySpec> {
+        let name = name.to_lowercase();
+        let Some(&factory) = self.factories.get(&name) else {
+            anyhow::bail!(
+                "Unknown strategy '{}' (available: {})",
+                name,
+                self.names().collect::<Vec<_>>().join(", ")
+            );
+        };
+        Ok(StrategySpec { name, factory, section })
+    }
+}
+
+/// A registered strategy with its config section, buildable for any `StrategyConfig` (e.g. per sweep grid point)
+#[derive(Clone)]
+pub struct StrategySpec {
+    pub name: String,
+    factory: StrategyFactory,
+    section: Option<serde_json::Value>,
+}
+
+impl StrategySpec {
+    pub fn build(&self, config: StrategyConfig) -> Result<Box<dyn Strategy>> {
+        (self.factory)(config, self.section.as_ref())
+    }
 }
@@ -0,0 +1,294 @@
+// Parameter sweep - grid search over StrategyConfig fields using parallel backtests
+
+use crate::backtest::Backtester;
+use crate::config::StrategyConfig;
+use crate::strategies::StrategySpec;
+use crate::recorder::SnapshotRecord;
+use anyhow::Result;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// StrategyConfig fields that can be swept
+pub const SWEEPABLE_FIELDS: &[&str] = &[
+    "trend_threshold",
+    "profit_threshold",
+    "sl_threshold",
+    "lookback",
+    "position_size",
+    "macd_fast_period",
+    "macd_slow_period",
+    "macd_signal_period",
+    "momentum_threshold_pct",
+    "use_macd_sl_filter",
+    "trading_start_when_remaining_minutes",
+    "band_std_dev",
+];
+
+/// Values to try for one StrategyConfig field
+#[derive(Debug, Clone)]
+pub struct ParamRange {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+impl FromStr for ParamRange {
+    type Err = anyhow::Error;
+
+    /// Parse `name=start:end:step` (inclusive) or `name=v1,v2,...`
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, spec) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid parameter '{}' (expected name=start:end:step or name=v1,v2)", s))?;
+        let name = name.trim().to_string();
+        if !SWEEPABLE_FIELDS.contains(&name.as_str()) {
+            anyhow::bail!("Unknown sweep parameter '{}' (supported: {})", name, SWEEPABLE_FIELDS.join(", "));
+        }
+
+        let parse = |v: &str| -> Result<f64> {
+            match v.trim() {
+                "true" => Ok(1.0),
+                "false" => Ok(0.0),
+                v => v.parse().map_err(|_| anyhow::anyhow!("Invalid value '{}' for {}", v, name)),
+            }
+        };
+
+        let values = if spec.contains(':') {
+            let parts: Vec<&str> = spec.split(':').collect();
+            let [start, end, step] = parts[..] else {
+                anyhow::bail!("Invalid range '{}' for {} (expected start:end:step)", spec, name);
+            };
+            let (start, end, step) = (parse(start)?, parse(end)?, parse(step)?);
+            if step <= 0.0 || end < start {
+                anyhow::bail!("Invalid range '{}' for {} (need step > 0 and end >= start)", spec, name);
+            }
+            // Count steps up front so float accumulation can't drop the end point
+            let steps = ((end - start) / step + 1e-9).floor() as usize;
+            (0..=steps).map(|i| start + step * i as f64).collect()
+        } else {
+            spec.split(',').map(parse).collect::<Result<Vec<_>>>()?
+        };
+
+        if values.is_empty() {
+            anyhow::bail!("No values for sweep parameter {}", name);
+        }
+        Ok(Self { name, values })
+    }
+}
+
+/// Set one StrategyConfig field from a sweep value
+pub fn apply_param(config: &mut StrategyConfig, name: &str, value: f64) -> Result<()> {
+    let decimal = || Decimal::from_f64(value).ok_or_else(|| anyhow::anyhow!("Invalid value {} for {}", value, name));
+    let count = || {
+        if value < 1.0 {
+            anyhow::bail!("{} must be at least 1, got {}", name, value);
+        }
+        Ok(value.round() as usize)
+    };
+    match name {
+        "trend_threshold" => config.trend_threshold = value,
+        "profit_threshold" => config.profit_threshold = decimal()?,
+        "sl_threshold" => config.sl_threshold = decimal()?,
+        "lookback" => config.lookback = count()?,
+        "position_size" => config.position_size_shares = decimal()?,
+        "macd_fast_period" => config.macd_fast_period = count()?,
+        "macd_slow_period" => config.macd_slow_period = count()?,
+        "macd_signal_period" => config.macd_signal_period = count()?,
+        "momentum_threshold_pct" => config.momentum_threshold_pct = value,
+        "use_macd_sl_filter" => config.use_macd_sl_filter = value != 0.0,
+        "trading_start_when_remaining_minutes" => {
+            config.trading_start_when_remaining_minutes = Some(value.max(0.0).round() as u64)
+        }
+        "band_std_dev" => config.band_std_dev = value,
+        _ => anyhow::bail!("Unknown sweep parameter '{}'", name),
+    }
+    Ok(())
+}
+
+/// One parameter combination: the swept values and the resulting config
+#[derive(Debug, Clone)]
+pub struct GridPoint {
+    pub params: Vec<(String, f64)>,
+    pub config: StrategyConfig,
+}
+
+/// Cartesian product of all ranges applied to `base` (invalid combinations are skipped)
+pub fn build_grid(base: &StrategyConfig, ranges: &[ParamRange]) -> Result<Vec<GridPoint>> {
+    let mut grid = vec![GridPoint { params: Vec::new(), config: base.clone() }];
+    for range in ranges {
+        let mut next = Vec::with_capacity(grid.len() * range.values.len());
+        for point in &grid {
+            for &value in &range.values {
+                let mut config = point.config.clone();
+                apply_param(&mut config, &range.name, value)?;
+                let mut params = point.params.clone();
+                params.push((range.name.clone(), value));
+                next.push(GridPoint { params, config });
+            }
+        }
+        grid = next;
+    }
+    // MACD needs a fast EMA shorter than the slow one
+    grid.retain(|p| p.config.macd_fast_period < p.config.macd_slow_period);
+    Ok(grid)
+}
+
+/// Metric used to rank sweep results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankMetric {
+    Pnl,
+    WinRate,
+    Drawdown,
+    Sharpe,
+}
+
+impl FromStr for RankMetric {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "pnl" => Ok(RankMetric::Pnl),
+            "win_rate" | "winrate" => Ok(RankMetric::WinRate),
+            "drawdown" | "max_drawdown" => Ok(RankMetric::Drawdown),
+            "sharpe" => Ok(RankMetric::Sharpe),
+            other => anyhow::bail!("Unknown rank metric '{}' (use pnl, win_rate, drawdown or sharpe)", other),
+        }
+    }
+}
+
+/// Backtest outcome for one parameter combination
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepResult {
+    /// Swept parameter values, in the order given on the command line
+    pub params: Vec<(String, f64)>,
+    #[serde(skip)]
+    pub config: StrategyConfig,
+    pub total_pnl: Decimal,
+    pub trades: usize,
+    pub win_rate: f64,
+    pub max_drawdown: Decimal,
+    pub sharpe: Option<f64>,
+}
+
+/// Backtest one config of `strategy` over `records`
+pub fn evaluate(
+    records: &Arc<[SnapshotRecord]>,
+    strategy: &StrategySpec,
+    grid_point: GridPoint,
+    initial_capital: Decimal,
+) -> Result<SweepResult> {
+    let backtester = Backtester::new(strategy.build(grid_point.config.clone())?, initial_capital);
+    let stats = backtester.run(records.clone())?.stats;
+    Ok(SweepResult {
+        params: grid_point.params,
+        config: grid_point.config,
+        total_pnl: stats.total_pnl,
+        trades: stats.total_trades,
+        win_rate: stats.win_rate(),
+        max_drawdown: stats.max_drawdown(),
+        sharpe: stats.sharpe_ratio(),
+    })
+}
+
+/// Backtest every grid entry, spread across `threads` worker threads (results in grid order)
+pub fn run_sweep(
+    records: &Arc<[SnapshotRecord]>,
+    strategy: &StrategySpec,
+    grid: Vec<GridPoint>,
+    initial_capital: Decimal,
+    threads: usize,
+) -> Result<Vec<SweepResult>> {
+    let threads = threads.max(1);
+    let chunk_size = grid.len().div_ceil(threads).max(1);
+    let mut chunks: Vec<Vec<GridPoint>> = Vec::new();
+    let mut grid = grid.into_iter().peekable();
+    while grid.peek().is_some() {
+        chunks.push(grid.by_ref().take(chunk_size).collect());
+    }
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .into_iter()
+                        .map(|grid_point| evaluate(records, strategy, grid_point, initial_capital))
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.extend(handle.join().expect("sweep worker panicked")?);
+        }
+        Ok(results)
+    })
+}
+
+/// Sort results best-first by `metric` (lowest drawdown is best; missing Sharpe ranks last)
+pub fn rank(results: &mut [SweepResult], metric: RankMetric) {
+    results.sort_by(|a, b| match metric {
+        RankMetric::Pnl => b.total_pnl.cmp(&a.total_pnl),
+        RankMetric::WinRate => b.win_rate.total_cmp(&a.win_rate),
+        RankMetric::Drawdown => a.max_drawdown.cmp(&b.max_drawdown),
+        RankMetric::Sharpe => b
+            .sharpe
+            .unwrap_or(f64::NEG_INFINITY)
+            .total_cmp(&a.sharpe.unwrap_or(f64::NEG_INFINITY)),
+    });
+}
+
+/// Write the leaderboard as JSON (`.json`) or CSV (anything else)
+pub fn write_leaderboard(path: &Path, results: &[SweepResult]) -> Result<()> {
+    let is_json = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let content = if is_json {
+        serde_json::to_string_pretty(results)?
+    } else {
+        let param_names: Vec<&str> = results
+            .first()
+            .map(|r| r.params.iter().map(|(name, _)| name.as_str()).collect())
+            .unwrap_or_default();
+        let mut csv = format!("rank,{},total_pnl,trades,win_rate,max_drawdown,sharpe\n", param_names.join(","));
+        for (i, r) in results.iter().enumerate() {
+            let params: Vec<String> = r.params.iter().map(|(_, v)| v.to_string()).collect();
+            csv.push_str(&format!(
+                "{},{},{},{},{:.2},{},{}\n",
+                i + 1,
+                params.join(","),
+                r.total_pnl,
+                r.trades,
+                r.win_rate,
+                r.max_drawdown,
+                r.sharpe.map(|s| format!("{:.4}", s)).unwrap_or_default()
+            ));
+        }
+        csv
+    };
+
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// One-line summary of a result for terminal output
+pub fn format_result(rank: usize, r: &SweepResult) -> String {
+    let params: Vec<String> = r.params.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+    format!(
+        "#{:<3} {} | pnl={:.4} | trades={} | win_rate={:.1}% | max_dd={:.4} | sharpe={}",
+        rank,
+        params.join(" "),
+        r.total_pnl,
+        r.trades,
+        r.win_rate,
+        r.max_drawdown,
+        r.sharpe.map(|s| format!("{:.3}", s)).unwrap_or_else(|| "n/a".to_string())
+    )
+}
@@ -1,23 +1,29 @@
 // Core types used throughout the trading system
 
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
-use rust_decimal_macros::dec;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+/// Length of one up/down market in seconds
+pub const MARKET_DURATION_SECS: u64 = 900;
 
 /// Price data point with both Up and Down token prices
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricePoint {
     pub timestamp: u64,
     pub up_price: f64,   // Up token price
     pub down_price: f64, // Down token price
+    #[serde(default)]
     pub actual_outcome: Option<u8>, // 1 for Up win, 0 for Down win, None if not specified
+    #[serde(default)]
     pub asset: Option<String>, // Asset identifier (e.g., "BTC", "ETH")
+    #[serde(default)]
     pub news_event: Option<i8>, // 1 for positive Up news, -1 for Down, 0 for none
+    #[serde(default)]
+    pub time_remaining_secs: Option<u64>, // Seconds left in the market (derived from the timestamp when missing)
 }
 
 /// Simple side enum for active trading cycles
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PositionSide {
     Flat,
     LongUp,
@@ -29,7 +35,7 @@ pub enum PositionSide {
 /// This is used by both simulation and live traders to ensure we only
 /// have exactly one open position per asset at a time, matching the
 /// backtest behavior: buy -> TP or SL hit -> flat again.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActiveCycle {
     pub side: PositionSide,
     /// Entry price of the token we bought
@@ -42,6 +48,80 @@ pub struct ActiveCycle {
     pub sl_price: Decimal,
 }
 
+impl ActiveCycle {
+    /// Open a cycle at `entry_price` with absolute TP/SL offsets (TP = entry + profit, SL = entry - sl)
+    pub fn open(side: PositionSide, entry_price: Decimal, size: Decimal, profit_threshold: Decimal, sl_threshold: Decimal) -> Self {
+        Self {
+            side,
+            entry_price,
+            size,
+            tp_price: entry_price + profit_threshold,
+            sl_price: entry_price - sl_threshold,
+        }
+    }
+
+    /// Prices of the held token and the opposite token at a price point
+    pub fn held_and_opposite(&self, point: &PricePoint) -> (f64, f64) {
+        match self.side {
+            PositionSide::LongUp => (point.up_price, point.down_price),
+            PositionSide::LongDown => (point.down_price, point.up_price),
+            PositionSide::Flat => (0.0, 0.0),
+        }
+    }
+
+    /// Take‑profit: the held token reached TP (only when TP is a valid price, i.e. <= 1.0)
+    pub fn tp_hit(&self, held_price: Decimal) -> bool {
+        self.tp_price <= Decimal::ONE && held_price >= self.tp_price
+    }
+
+    /// Stop‑loss: the opposite token's ask reached (1 - SL), i.e. the held token dropped to SL
+    pub fn sl_hit(&self, opposite_price: Decimal) -> bool {
+        opposite_price >= Decimal::ONE - self.sl_price
+    }
+
+    pub fn tp_pnl(&self) -> Decimal {
+        (self.tp_price - self.entry_price) * self.size
+    }
+
+    pub fn sl_pnl(&self) -> Decimal {
+        (self.sl_price - self.entry_price) * self.size
+    }
+
+    /// PnL and win flag when the market resolves: the winning token pays 1.0, the other 0.0.
+    /// `winner` is `Flat` when the outcome is unknown, which settles as a loss.
+    pub fn settle(&self, winner: PositionSide) -> (Decimal, bool) {
+        if self.side != PositionSide::Flat && self.side == winner {
+            ((Decimal::ONE - self.entry_price) * self.size, true)
+        } else if self.side == PositionSide::Flat {
+            (Decimal::ZERO, false)
+        } else {
+            ((Decimal::ZERO - self.entry_price) * self.size, false)
+        }
+    }
+}
+
+impl PositionSide {
+    /// Winning side inferred from final prices (a resolved token trades at ≈ 1.0)
+    pub fn winner_from_prices(up_price: f64, down_price: f64) -> Self {
+        if up_price >= 0.99 {
+            PositionSide::LongUp
+        } else if down_price >= 0.99 {
+            PositionSide::LongDown
+        } else {
+            PositionSide::Flat
+        }
+    }
+
+    /// The other token (a stop loss buys it to hedge the held one)
+    pub fn opposite(self) -> Self {
+        match self {
+            PositionSide::LongUp => PositionSide::LongDown,
+            PositionSide::LongDown => PositionSide::LongUp,
+            PositionSide::Flat => PositionSide::Flat,
+        }
+    }
+}
+
 impl PricePoint {
     /// Get price for backward compatibility (returns Up price)
     pub fn price(&self) -> f64 {
@@ -50,7 +130,7 @@ impl PricePoint {
 }
 
 /// Position state during trading
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PositionState {
     NoPosition,
     LongUp {
@@ -73,8 +153,171 @@ pub enum PositionState {
     },
 }
 
+impl PositionState {
+    /// Hedged position from each leg's fill price and size, with `fee_rate` of the notional added to each leg's cost
+    pub fn hedged(up_price: Decimal, up_size: Decimal, down_price: Decimal, down_size: Decimal, fee_rate: Decimal) -> Self {
+        PositionState::Hedged {
+            up_buy_price: up_price,
+            up_size,
+            up_cost: up_price * up_size * (Decimal::ONE + fee_rate),
+            down_buy_price: down_price,
+            down_size,
+            down_cost: down_price * down_size * (Decimal::ONE + fee_rate),
+        }
+    }
+
+    /// Add a filled BUY of one token to the position, keeping both legs (inventory of a market maker).
+    /// Buy prices become size-weighted averages.
+    pub fn add_fill(&mut self, side: PositionSide, price: Decimal, size: Decimal) {
+        if side == PositionSide::Flat || size <= Decimal::ZERO {
+            return;
+        }
+        let (mut up_size, mut up_cost, mut down_size, mut down_cost) = match self {
+            PositionState::NoPosition => (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO),
+            PositionState::LongUp { size, cost, .. } => (*size, *cost, Decimal::ZERO, Decimal::ZERO),
+            PositionState::LongDown { size, cost, .. } => (Decimal::ZERO, Decimal::ZERO, *size, *cost),
+            PositionState::Hedged { up_size, up_cost, down_size, down_cost, .. } => (*up_size, *up_cost, *down_size, *down_cost),
+        };
+        if side == PositionSide::LongUp {
+            up_size += size;
+            up_cost += price * size;
+        } else {
+            down_size += size;
+            down_cost += price * size;
+        }
+        let average = |cost: Decimal, size: Decimal| if size.is_zero() { Decimal::ZERO } else { cost / size };
+        *self = PositionState::Hedged {
+            up_buy_price: average(up_cost, up_size),
+            up_size,
+            up_cost,
+            down_buy_price: average(down_cost, down_size),
+            down_size,
+            down_cost,
+        };
+    }
+
+    /// Remove up to `size` shares of one token (sold) and return the cost they carried.
+    /// The leg gives up its average cost for the removed shares.
+    pub fn remove_shares(&mut self, side: PositionSide, size: Decimal) -> Decimal {
+        let size = size.min(self.shares(side));
+        if size <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let (mut up_size, mut up_cost, mut down_size, mut down_cost) = match self {
+            PositionState::NoPosition => return Decimal::ZERO,
+            PositionState::LongUp { size, cost, .. } => (*size, *cost, Decimal::ZERO, Decimal::ZERO),
+            PositionState::LongDown { size, cost, .. } => (Decimal::ZERO, Decimal::ZERO, *size, *cost),
+            PositionState::Hedged { up_size, up_cost, down_size, down_cost, .. } => (*up_size, *up_cost, *down_size, *down_cost),
+        };
+        let (leg_size, leg_cost) = if side == PositionSide::LongUp { (&mut up_size, &mut up_cost) } else { (&mut down_size, &mut down_cost) };
+        let removed_cost = *leg_cost * size / *leg_size;
+        *leg_size -= size;
+        *leg_cost -= removed_cost;
+        let average = |cost: Decimal, size: Decimal| if size.is_zero() { Decimal::ZERO } else { cost / size };
+        *self = if up_size.is_zero() && down_size.is_zero() {
+            PositionState::NoPosition
+        } else {
+            PositionState::Hedged {
+                up_buy_price: average(up_cost, up_size),
+                up_size,
+                up_cost,
+                down_buy_price: average(down_cost, down_size),
+                down_size,
+                down_cost,
+            }
+        };
+        removed_cost
+    }
+
+    /// Shares held of one token
+    pub fn shares(&self, side: PositionSide) -> Decimal {
+        match (self, side) {
+            (PositionState::LongUp { size, .. }, PositionSide::LongUp)
+            | (PositionState::LongDown { size, .. }, PositionSide::LongDown)
+            | (PositionState::Hedged { up_size: size, .. }, PositionSide::LongUp)
+            | (PositionState::Hedged { down_size: size, .. }, PositionSide::LongDown) => *size,
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Up shares minus Down shares
+    pub fn net_up_shares(&self) -> Decimal {
+        match self {
+            PositionState::NoPosition => Decimal::ZERO,
+            PositionState::LongUp { size, .. } => *size,
+            PositionState::LongDown { size, .. } => -*size,
+            PositionState::Hedged { up_size, down_size, .. } => *up_size - *down_size,
+        }
+    }
+
+    /// Up+Down pairs held (each pays exactly 1.0 at resolution or when merged)
+    pub fn matched_pairs(&self) -> Decimal {
+        match self {
+            PositionState::Hedged { up_size, down_size, .. } => (*up_size).min(*down_size),
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Remove up to `pairs` matched pairs (merged back to 1.0 USDC each) and return their realized PnL.
+    /// Each leg gives up its average cost for the merged shares.
+    pub fn merge(&mut self, pairs: Decimal) -> Decimal {
+        let pairs = pairs.min(self.matched_pairs());
+        let PositionState::Hedged { up_size, up_cost, down_size, down_cost, .. } = self else {
+            return Decimal::ZERO;
+        };
+        if pairs <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let merged_up_cost = *up_cost * pairs / *up_size;
+        let merged_down_cost = *down_cost * pairs / *down_size;
+        *up_size -= pairs;
+        *up_cost -= merged_up_cost;
+        *down_size -= pairs;
+        *down_cost -= merged_down_cost;
+        if up_size.is_zero() && down_size.is_zero() {
+            *self = PositionState::NoPosition;
+        }
+        pairs - merged_up_cost - merged_down_cost
+    }
+
+    pub fn is_open(&self) -> bool {
+        !matches!(self, PositionState::NoPosition)
+    }
+
+    /// Total USD paid for the position (fees included)
+    pub fn cost(&self) -> Decimal {
+        match self {
+            PositionState::NoPosition => Decimal::ZERO,
+            PositionState::LongUp { cost, .. } | PositionState::LongDown { cost, .. } => *cost,
+            PositionState::Hedged { up_cost, down_cost, .. } => *up_cost + *down_cost,
+        }
+    }
+
+    /// PnL guaranteed whichever side wins: matched Up+Down pairs pay 1.0, any unmatched shares count as lost
+    pub fn locked_profit(&self) -> Decimal {
+        match self {
+            PositionState::Hedged { up_size, down_size, .. } => (*up_size).min(*down_size) - self.cost(),
+            _ => -self.cost(),
+        }
+    }
+
+    /// PnL when the market resolves: the winning token pays 1.0, the other 0.0.
+    /// An unknown outcome (`Flat`) settles at the locked-in (worst-case) profit.
+    pub fn settle(&self, winner: PositionSide) -> Decimal {
+        let payout = match (self, winner) {
+            (PositionState::LongUp { size, .. }, PositionSide::LongUp)
+            | (PositionState::LongDown { size, .. }, PositionSide::LongDown)
+            | (PositionState::Hedged { up_size: size, .. }, PositionSide::LongUp)
+            | (PositionState::Hedged { down_size: size, .. }, PositionSide::LongDown) => *size,
+            (_, PositionSide::Flat) => return self.locked_profit(),
+            _ => Decimal::ZERO,
+        };
+        payout - self.cost()
+    }
+}
+
 /// Trading statistics
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct TradingStats {
     pub total_trades: usize,
     pub winning_trades: usize,
@@ -95,6 +338,45 @@ impl TradingStats {
     pub fn add_equity_point(&mut self, timestamp: u64, equity: Decimal) {
         self.equity_curve.push((timestamp, equity));
     }
+
+    /// Largest peak-to-trough fall of the equity curve (starting from the initial capital)
+    pub fn max_drawdown(&self) -> Decimal {
+        let mut peak = self.current_capital - self.total_pnl;
+        let mut max_drawdown = Decimal::ZERO;
+        for &(_, equity) in &self.equity_curve {
+            peak = peak.max(equity);
+            max_drawdown = max_drawdown.max(peak - equity);
+        }
+        max_drawdown
+    }
+
+    /// Per-trade Sharpe ratio: mean / standard deviation of trade PnL (not annualized).
+    /// None with fewer than two trades or zero variance.
+    pub fn sharpe_ratio(&self) -> Option<f64> {
+        use rust_decimal::prelude::ToPrimitive;
+        let mut previous = self.current_capital - self.total_pnl;
+        let returns: Vec<f64> = self
+            .equity_curve
+            .iter()
+            .map(|&(_, equity)| {
+                let pnl = (equity - previous).to_f64().unwrap_or(0.0);
+                previous = equity;
+                pnl
+            })
+            .collect();
+        if returns.len() < 2 {
+            return None;
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let std_dev = variance.sqrt();
+        if std_dev > 0.0 {
+            Some(mean / std_dev)
+        } else {
+            None
+        }
+    }
 }
 
 /// Trade log entry
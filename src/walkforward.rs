@@ -0,0 +1,198 @@
+// Walk-forward optimization - optimize on a rolling in-sample window, validate out-of-sample
+
+use crate::backtest::Backtester;
+use crate::recorder::SnapshotRecord;
+use crate::strategies::StrategySpec;
+use crate::sweep::{self, GridPoint, RankMetric, SweepResult};
+use crate::types::TradingStats;
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Window sizes (in 15-minute markets) and selection rules
+#[derive(Debug, Clone)]
+pub struct WalkForwardConfig {
+    pub in_sample_markets: usize,
+    pub out_of_sample_markets: usize,
+    /// Markets to advance between windows (usually = out_of_sample_markets)
+    pub step_markets: usize,
+    pub metric: RankMetric,
+    /// Candidates with fewer in-sample trades are not eligible (guards against one-trade flukes)
+    pub min_trades: usize,
+    /// Minimum walk-forward efficiency (OOS PnL/market ÷ IS PnL/market) to pass
+    pub min_efficiency: f64,
+    pub threads: usize,
+}
+
+/// One in-sample optimization + out-of-sample evaluation
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowResult {
+    pub window: usize,
+    /// First/last period (UNIX seconds) of each window, inclusive
+    pub in_sample: (u64, u64),
+    pub out_of_sample: (u64, u64),
+    /// Best in-sample result (its params are the ones carried forward)
+    pub best_in_sample: SweepResult,
+    pub oos_pnl: Decimal,
+    pub oos_trades: usize,
+    pub oos_win_rate: f64,
+    /// OOS PnL per market ÷ IS PnL per market (None when IS PnL <= 0)
+    pub efficiency: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalkForwardReport {
+    pub windows: Vec<WindowResult>,
+    /// Windows skipped because no candidate met `min_trades` in-sample
+    pub skipped_windows: usize,
+    /// Stitched out-of-sample performance across all windows
+    pub oos_stats: TradingStats,
+    pub oos_max_drawdown: Decimal,
+    pub oos_sharpe: Option<f64>,
+    /// Aggregate walk-forward efficiency over all evaluated windows
+    pub efficiency: Option<f64>,
+    /// Overfitting guard verdict: OOS profitable and efficiency >= min_efficiency
+    pub passed: bool,
+}
+
+/// Snapshots whose 15-minute period falls in `periods`
+fn records_in(records: &[SnapshotRecord], periods: &[u64]) -> Arc<[SnapshotRecord]> {
+    let (Some(&first), Some(&last)) = (periods.first(), periods.last()) else {
+        return Arc::from(Vec::new());
+    };
+    records
+        .iter()
+        .filter(|r| r.period_timestamp >= first && r.period_timestamp <= last)
+        .cloned()
+        .collect()
+}
+
+/// Run walk-forward analysis of `strategy` over `grid` and `records` (in capture order)
+pub fn run_walk_forward(
+    records: &[SnapshotRecord],
+    strategy: &StrategySpec,
+    grid: &[GridPoint],
+    initial_capital: Decimal,
+    config: &WalkForwardConfig,
+) -> Result<WalkForwardReport> {
+    if config.in_sample_markets == 0 || config.out_of_sample_markets == 0 || config.step_markets == 0 {
+        anyhow::bail!("Walk-forward window sizes and step must be at least one market");
+    }
+    // Overlapping out-of-sample windows would count the same markets twice in the stitched result
+    if config.step_markets < config.out_of_sample_markets {
+        anyhow::bail!(
+            "Walk-forward step ({} markets) must be at least the out-of-sample window ({} markets)",
+            config.step_markets,
+            config.out_of_sample_markets
+        );
+    }
+
+    let mut periods: Vec<u64> = records.iter().map(|r| r.period_timestamp).collect();
+    periods.dedup();
+
+    let window_len = config.in_sample_markets + config.out_of_sample_markets;
+    if periods.len() < window_len {
+        anyhow::bail!(
+            "Need at least {} markets for one walk-forward window, data has {}",
+            window_len,
+            periods.len()
+        );
+    }
+
+    let mut windows = Vec::new();
+    let mut skipped_windows = 0;
+    let mut oos_stats = TradingStats {
+        current_capital: initial_capital,
+        ..TradingStats::default()
+    };
+    let (mut is_pnl_per_market, mut oos_pnl_per_market) = (0.0, 0.0);
+
+    let mut start = 0;
+    while start + window_len <= periods.len() {
+        let is_periods = &periods[start..start + config.in_sample_markets];
+        let oos_periods = &periods[start + config.in_sample_markets..start + window_len];
+        start += config.step_markets;
+
+        // Optimize in-sample
+        let is_records = records_in(records, is_periods);
+        let mut candidates = sweep::run_sweep(&is_records, strategy, grid.to_vec(), initial_capital, config.threads)?;
+        candidates.retain(|r| r.trades >= config.min_trades);
+        sweep::rank(&mut candidates, config.metric);
+        let Some(best) = candidates.into_iter().next() else {
+            skipped_windows += 1;
+            continue;
+        };
+
+        // Evaluate the chosen params on the following unseen markets
+        let oos_records = records_in(records, oos_periods);
+        let report = Backtester::new(strategy.build(best.config.clone())?, initial_capital).run(oos_records)?;
+
+        // Stitch this window's trades onto the combined OOS equity curve
+        let mut previous = initial_capital;
+        for &(ts, equity) in &report.stats.equity_curve {
+            let pnl = equity - previous;
+            previous = equity;
+            oos_stats.total_pnl += pnl;
+            oos_stats.current_capital = initial_capital + oos_stats.total_pnl;
+            let capital = oos_stats.current_capital;
+            oos_stats.add_equity_point(ts, capital);
+        }
+        oos_stats.total_trades += report.stats.total_trades;
+        oos_stats.winning_trades += report.stats.winning_trades;
+        oos_stats.losing_trades += report.stats.losing_trades;
+
+        let is_per_market = best.total_pnl.to_f64().unwrap_or(0.0) / is_periods.len() as f64;
+        let oos_per_market = report.stats.total_pnl.to_f64().unwrap_or(0.0) / oos_periods.len() as f64;
+        is_pnl_per_market += is_per_market;
+        oos_pnl_per_market += oos_per_market;
+
+        windows.push(WindowResult {
+            window: windows.len() + 1,
+            in_sample: (is_periods[0], is_periods[is_periods.len() - 1]),
+            out_of_sample: (oos_periods[0], oos_periods[oos_periods.len() - 1]),
+            oos_pnl: report.stats.total_pnl,
+            oos_trades: report.stats.total_trades,
+            oos_win_rate: report.stats.win_rate(),
+            efficiency: (is_per_market > 0.0).then(|| oos_per_market / is_per_market),
+            best_in_sample: best,
+        });
+    }
+
+    let efficiency = (is_pnl_per_market > 0.0).then(|| oos_pnl_per_market / is_pnl_per_market);
+    let passed = !windows.is_empty()
+        && oos_stats.total_pnl > Decimal::ZERO
+        && efficiency.is_some_and(|e| e >= config.min_efficiency);
+
+    Ok(WalkForwardReport {
+        oos_max_drawdown: oos_stats.max_drawdown(),
+        oos_sharpe: oos_stats.sharpe_ratio(),
+        windows,
+        skipped_windows,
+        oos_stats,
+        efficiency,
+        passed,
+    })
+}
+
+/// One-line summary of a window for terminal output
+pub fn format_window(w: &WindowResult) -> String {
+    let params: Vec<String> = w.best_in_sample.params.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+    format!(
+        "W{:<3} IS {}..{} pnl={:.4} ({} trades) → OOS {}..{} pnl={:.4} ({} trades, {:.1}% win) | eff={} | {}",
+        w.window,
+        w.in_sample.0,
+        w.in_sample.1,
+        w.best_in_sample.total_pnl,
+        w.best_in_sample.trades,
+        w.out_of_sample.0,
+        w.out_of_sample.1,
+        w.oos_pnl,
+        w.oos_trades,
+        w.oos_win_rate,
+        w.efficiency.map(|e| format!("{:.2}", e)).unwrap_or_else(|| "n/a".to_string()),
+        params.join(" ")
+    )
+}
+